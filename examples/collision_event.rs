@@ -5,13 +5,9 @@ use amethyst::{
     assets::AssetLoaderSystemData,
     core::{
         SystemBundle,
-        EventReader,
         transform::{Transform, TransformBundle},
-        ecs::{DispatcherBuilder, ReaderId},
-        shrev::EventChannel,
+        ecs::DispatcherBuilder,
     },
-    derive::EventReader,
-    ecs::{Read, SystemData},
     input::{get_key, is_close_requested, is_key_down, VirtualKeyCode},
     prelude::*,
     renderer::{
@@ -33,6 +29,9 @@ use amethyst::{
 };
 use amethyst_rhusics::{
     DefaultPhysicsBundle2,
+    PhysicsApplication2,
+    PhysicsStateEvent2,
+    PhysicsUnits,
     PoseTransformSyncSystem2,
     setup_2d_arena,
     time_sync,
@@ -43,25 +42,23 @@ use amethyst_rhusics::{
         collide2d::{BodyPose2, CollisionShape2},
         physics2d::{Velocity2, Mass2},
     },
-    rhusics_ecs::{
-        WithPhysics,
-        physics2d::{CurrentFrameUpdateSystem2, NextFrameSetupSystem2},
-        collide2d::ContactEvent2,
-    },
+    rhusics_ecs::WithPhysics,
 };
 
 use log::info;
-use cgmath::{Point2, Basis2, EuclideanSpace, Vector2, One, Vector3, BaseNum};
+use cgmath::{Point2, Basis2, EuclideanSpace, Vector2, One, Vector3};
 use rhusics_core::Collider;
 use amethyst_core::ecs::{Component, DenseVecStorage, NullStorage};
-use amethyst::input::{StringBindings, BindingTypes, InputEvent};
-use amethyst::winit::Event;
-use amethyst::ui::UiEvent;
-use std::fmt::Debug;
 use crate::RacquetType::{RogerFederer, SerenaWilliams};
 use amethyst::core::ecs::Join;
 use rhusics_core::physics2d::ForceAccumulator2;
 
+/// Number of pixels per meter. Physics runs in meters, while the scene is laid out in pixels.
+const PIXELS_PER_METER: f32 = 50.;
+
+/// Vertical gravity in meters per second squared. Only a slight pull, so the ball drops a little
+/// between the racquets instead of falling out of play; -0.095 pixels per second squared.
+const GRAVITY: f32 = -0.095 / PIXELS_PER_METER;
 
 /// Shape type for collision and positioning.
 type MyWorldParameters = WorldParameters<<Point2<f32> as EuclideanSpace>::Diff, <Point2<f32> as EuclideanSpace>::Scalar>;
@@ -148,10 +145,11 @@ fn register_components(world: &mut World) {
 //                    Game Resources
 //
 // =================================================================================================
-/// The default World Parameters has no gravity. Keep it that way.
+/// The default World Parameters has no gravity, add a slight one (see `GRAVITY`).
 /// This also sets damping to 1.0 to turn it off.
 fn add_resources(world: &mut World) {
-    world.insert(MyWorldParameters::new(Vector2::new(0.0, -0.095)).with_damping(1.0));
+    world.insert(MyWorldParameters::new(Vector2::new(0.0, GRAVITY)).with_damping(1.0));
+    world.insert(PhysicsUnits::new(PIXELS_PER_METER));
 }
 
 // =================================================================================================
@@ -208,11 +206,11 @@ fn add_racquet(world: &mut World, dimensions: &ScreenDimensions, racquet_type: R
             CollisionShape2::<f32, BodyPose2<f32>, CollisionType>::new_simple_with_type(
                 CollisionStrategy::FullResolution,
                 CollisionMode::Discrete,
-                Rectangle::new(scale[0]*2., scale[1]*2.).into(),
+                Rectangle::new(scale[0]*2. / PIXELS_PER_METER, scale[1]*2. / PIXELS_PER_METER).into(),
                 CollisionType::Racquet
             ),
             BodyPose2::<f32>::new(
-                Point2::new(x, y),
+                Point2::new(x / PIXELS_PER_METER, y / PIXELS_PER_METER),
                 Basis2::one(),
             ),
             PhysicalEntity::default(),
@@ -272,11 +270,11 @@ fn add_ball(world: &mut World, dimensions: &ScreenDimensions) {
             CollisionShape2::<f32, BodyPose2<f32>, CollisionType>::new_simple_with_type(
                 CollisionStrategy::FullResolution,
                 CollisionMode::Discrete,
-                Rectangle::new(scale[0]*2., scale[1]*2.).into(),
+                Rectangle::new(scale[0]*2. / PIXELS_PER_METER, scale[1]*2. / PIXELS_PER_METER).into(),
                 CollisionType::Ball
             ),
             BodyPose2::<f32>::new(
-                Point2::new(x, y),
+                Point2::new(x / PIXELS_PER_METER, y / PIXELS_PER_METER),
                 Basis2::one(),
             ),
             Velocity2::<f32>::default(),
@@ -342,39 +340,11 @@ struct GameBundle;
 /// current position.
 impl <'a, 'b>SystemBundle<'a, 'b> for GameBundle {
     fn build(self, _world: &mut World, builder: &mut DispatcherBuilder<'a, 'b>) -> amethyst::Result<()> {
-        builder.add(CurrentFrameUpdateSystem2::<f32, BodyPose2<f32>>::new(), "current frame", &[]);
-        builder.add(NextFrameSetupSystem2::<f32, BodyPose2<f32>>::new(), "next frame", &["current frame"]);
         builder.add(PoseTransformSyncSystem2::new().without_rotation(), "sync system", &[]);
         Ok(())
     }
 }
 
-// =================================================================================================
-//
-//                    Game Application
-//
-// =================================================================================================
-#[derive(Clone, Debug, EventReader)]
-#[reader(RhusicsStateEventReader)]
-pub enum RhusicsStateEvent<T = StringBindings, S = f32>
-    where
-        T: BindingTypes + Clone,
-        S: BaseNum + Clone + Copy + Debug + Send + Sync + 'static
-{
-    /// Events sent by the winit window.
-    Window(Event),
-    /// Events sent by the ui system.
-    Ui(UiEvent),
-    /// Events sent by the input system.
-    Input(InputEvent<T>),
-    /// Collision events
-    Collision(ContactEvent2<S>),
-}
-
-// pub type Application<'a, T> = CoreApplication<'a, T, StateEvent, StateEventReader>;
-pub type RhusicsApplication<'a, T> = CoreApplication<'a, T, RhusicsStateEvent, RhusicsStateEventReader>;
-
-
 // =================================================================================================
 //
 //                    Game State
@@ -386,7 +356,7 @@ pub type RhusicsApplication<'a, T> = CoreApplication<'a, T, RhusicsStateEvent, R
 struct GameState;
 
 //impl SimpleState for GameState {
-impl<'a, 'b> State<GameData<'a, 'b>, RhusicsStateEvent> for GameState {
+impl<'a, 'b> State<GameData<'a, 'b>, PhysicsStateEvent2> for GameState {
     // Here, we define hooks that will be called throughout the lifecycle of our game state.
     //
     // In this example, `on_start` is used for initializing entities
@@ -432,7 +402,7 @@ impl<'a, 'b> State<GameData<'a, 'b>, RhusicsStateEvent> for GameState {
         let mut forces = world.write_storage::<ForceAccumulator2<f32>>();
         for (_ball, _velocity, force) in (&balls, &mut velocities, &mut forces).join() {
             info!("Setting velocity");
-            force.add_force(Vector2::new(5000.0,0.0));
+            force.add_force(Vector2::new(5000.0 / PIXELS_PER_METER, 0.0));
             //velocity.set_linear(Vector2::new(10., 0.));
         }
     }
@@ -445,9 +415,9 @@ impl<'a, 'b> State<GameData<'a, 'b>, RhusicsStateEvent> for GameState {
         &mut self,
         // mut _data: StateData<'_, GameData<'_, '_>>,
         _data: StateData<'_, GameData<'_,'_>>,
-        event: RhusicsStateEvent,
-    ) -> Trans<GameData<'a, 'b>, RhusicsStateEvent> {
-        if let RhusicsStateEvent::Window(event) = &event {
+        event: PhysicsStateEvent2,
+    ) -> Trans<GameData<'a, 'b>, PhysicsStateEvent2> {
+        if let PhysicsStateEvent2::Window(event) = &event {
             // Check if the window should be closed
             if is_close_requested(&event) || is_key_down(&event, VirtualKeyCode::Escape) {
                 return Trans::Quit;
@@ -458,7 +428,7 @@ impl<'a, 'b> State<GameData<'a, 'b>, RhusicsStateEvent> for GameState {
                 info!("handling key event: {:?}", event);
             }
         }
-        else if let RhusicsStateEvent::Collision(collision) = &event {
+        else if let PhysicsStateEvent2::Collision(collision) = &event {
             info!("Collision detected!");
 
             // The contact info is the details about the collision.
@@ -514,44 +484,11 @@ impl<'a, 'b> State<GameData<'a, 'b>, RhusicsStateEvent> for GameState {
     ///
     /// Note the call to time_sync() to keep Rhusics' time component
     /// in sync with Amethyst's.
-    fn update(&mut self, data: StateData<'_, GameData<'a, 'b>>) -> Trans<GameData<'a, 'b>, RhusicsStateEvent> {
-        // Next line part of the kludge below
-        use rhusics_core::PhysicsTime;
-
+    fn update(&mut self, data: StateData<'_, GameData<'a, 'b>>) -> Trans<GameData<'a, 'b>, PhysicsStateEvent2> {
+        // The physics bundle keeps the force added in on_start until
+        // time starts moving, so there's nothing else to do here.
         time_sync(data.world);
-
-        // Kludge to get over a bug in rhusics, that force is consumed even
-        // when DT is 0. Save the force value, and, re-instate it after, if DT is 0.
-        // Note this code assumes there is only one ball object.
-        let time = (*data.world.read_resource::<rhusics_ecs::DeltaTime<f32>>()).delta_seconds();
-        //+info!("GameState::Update: Time is: {:?}", time);
-        let saved_force = if time == 0. {
-            let mut saved_force = None;
-            let balls = data.world.read_storage::<BallObjectType>();
-            let velocities = data.world.read_storage::<Velocity2<f32>>();
-            let mut forces = data.world.write_storage::<ForceAccumulator2<f32>>();
-            for (_ball, velocity, force) in (&balls, &velocities, &mut forces).join() {
-                info!("Before update: velocity = {:?}, force = {:?}", velocity, force);
-                saved_force = Some(force.consume_force()) // unfortunately, there's no non-destructive read
-            }
-            saved_force
-        } else { None };
-
         data.data.update(data.world);
-
-        // Re-instate the force saved above if dt is still 0.
-        let time = (*data.world.read_resource::<rhusics_ecs::DeltaTime<f32>>()).delta_seconds();
-        if time == 0. {
-            if let Some(force_vector) = saved_force {
-                let balls = data.world.read_storage::<BallObjectType>();
-                let velocities = data.world.read_storage::<Velocity2<f32>>();
-                let mut forces = data.world.write_storage::<ForceAccumulator2<f32>>();
-                for (_ball, velocity, force) in (&balls, &velocities, &mut forces).join() {
-                    force.add_force(force_vector);
-                    info!("After update:  velocity = {:?}, force = {:?}", velocity, force);
-                }
-            }
-        }
         Trans::None
     }
 
@@ -586,7 +523,7 @@ fn main() -> amethyst::Result<()> {
                 .with_plugin(RenderFlat3D::default()),
         )?;
 
-    let mut game = RhusicsApplication::build(
+    let mut game = PhysicsApplication2::build(
         resources,
         GameState::default()
         )?
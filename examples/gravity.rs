@@ -27,13 +27,14 @@ use amethyst::{
 };
 use amethyst_rhusics::{
     DefaultPhysicsBundle2,
+    PhysicsUnits,
     PoseTransformSyncSystem2,
     setup_2d_arena,
     time_sync,
     collision::CollisionStrategy,
     collision::primitive::Rectangle,
     rhusics_core::{
-        WorldParameters, CollisionMode, Pose, PhysicalEntity,
+        CollisionMode, Pose, PhysicalEntity,
         collide2d::{BodyPose2, CollisionShape2},
         physics2d::{Velocity2, Mass2},
     },
@@ -45,13 +46,13 @@ use amethyst_rhusics::{
 };
 
 use log::info;
-use cgmath::{Point2, Basis2, EuclideanSpace, Vector2, One, Vector3};
+use cgmath::{Point2, Basis2, One, Vector3};
 
 mod boxes;
 use boxes::ObjectType;
 
-/// Shape type for collision and positioning.
-type MyWorldParameters = WorldParameters<<Point2<f32> as EuclideanSpace>::Diff, <Point2<f32> as EuclideanSpace>::Scalar>;
+/// Number of pixels per meter. Physics runs in meters, so gravity is in m/s².
+const PIXELS_PER_METER: f32 = 30.;
 
 // =================================================================================================
 //
@@ -61,7 +62,6 @@ type MyWorldParameters = WorldParameters<<Point2<f32> as EuclideanSpace>::Diff,
 /// Registers the various components we need that are not automatically
 /// registered by the systems.
 fn register_components(world: &mut World) {
-    world.register::<CollisionShape2<f32, BodyPose2<f32>, ()>>();
     //world.register::<Material>();
     world.register::<ObjectType>();
 }
@@ -71,10 +71,9 @@ fn register_components(world: &mut World) {
 //                    Game Resources
 //
 // =================================================================================================
-/// The default World Parameters has no gravity. Add some.
-/// This also sets damping to 1.0 to turn it off.
+/// Gravity and damping are set in `physics_config.ron`, loaded by the physics bundle.
 fn add_resources(world: &mut World) {
-    world.insert(MyWorldParameters::new(Vector2::new(0.0, -9.8)).with_damping(1.0));
+    world.insert(PhysicsUnits::new(PIXELS_PER_METER));
 }
 
 // =================================================================================================
@@ -135,10 +134,10 @@ fn add_rectangle(world: &mut World, dimensions: &ScreenDimensions) {
             CollisionShape2::<f32, BodyPose2<f32>, ObjectType>::new_simple(
                 CollisionStrategy::FullResolution,
                 CollisionMode::Discrete,
-                Rectangle::new(scale[0]*2., scale[1]*2.).into(),
+                Rectangle::new(scale[0]*2. / PIXELS_PER_METER, scale[1]*2. / PIXELS_PER_METER).into(),
             ),
             BodyPose2::<f32>::new(
-                Point2::new(x, y),
+                Point2::new(x / PIXELS_PER_METER, y / PIXELS_PER_METER),
                 Basis2::one(),
             ),
             Velocity2::<f32>::default(),
@@ -282,10 +281,10 @@ fn add_static_rectangle(world: &mut World, dimensions: &ScreenDimensions) {
             CollisionShape2::<f32, BodyPose2<f32>, ObjectType>::new_simple(
                 CollisionStrategy::FullResolution,
                 CollisionMode::Discrete,
-                Rectangle::new(scale[0]*2., scale[1]*2.).into(),
+                Rectangle::new(scale[0]*2. / PIXELS_PER_METER, scale[1]*2. / PIXELS_PER_METER).into(),
             ),
             BodyPose2::<f32>::new(
-                Point2::new(x, y),
+                Point2::new(x / PIXELS_PER_METER, y / PIXELS_PER_METER),
                 Basis2::one(),
             ),
             PhysicalEntity::default(),
@@ -484,9 +483,10 @@ fn main() -> amethyst::Result<()> {
 
     let resources = app_root.join("examples/resources");
     let display_config = resources.join("display_config.ron");
+    let physics_config = resources.join("physics_config.ron");
 
     let game_data = GameDataBuilder::default()
-        .with_bundle(DefaultPhysicsBundle2::<ObjectType>::new().with_spatial())?
+        .with_bundle(DefaultPhysicsBundle2::<ObjectType>::from_config_path(physics_config)?)?
         .with_bundle(GameBundle)?
         .with_bundle(TransformBundle::new())?
         .with_bundle(
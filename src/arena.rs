@@ -1,20 +1,226 @@
-use amethyst_core::ecs::{Builder, World, WorldExt};
-use cgmath::{Basis2, Deg, EuclideanSpace, One, Point2, Point3, Quaternion, Rotation3, Vector2};
+use amethyst_core::ecs::{Builder, Entity, World, WorldExt};
+use cgmath::{
+    Basis2, Deg, EuclideanSpace, One, Point2, Point3, Quaternion, Rotation3, Transform, Vector2,
+};
 use collision::primitive::{Primitive2, Primitive3, Quad};
-use collision::{Aabb2, Aabb3, Line2};
-use rhusics_core::{CollisionMode, CollisionShape, CollisionStrategy, PhysicalEntity, Pose};
+use collision::{Aabb2, Aabb3, Bound, ComputeBound, Line2, Primitive, Union};
+use rhusics_core::{
+    CollisionMode, CollisionShape, CollisionStrategy, NextFrame, PhysicalEntity, Pose,
+};
 use rhusics_ecs::physics2d::{BodyPose2, Mass2};
 use rhusics_ecs::physics3d::{BodyPose3, Mass3};
 use rhusics_ecs::WithPhysics;
 
+use crate::units::PhysicsUnits;
+
+type Shape2<Y> = CollisionShape<Primitive2<f32>, BodyPose2<f32>, Aabb2<f32>, Y>;
+type Shape3<Y> = CollisionShape<Primitive3<f32>, BodyPose3<f32>, Aabb3<f32>, Y>;
+
+/// Handle to the walls of a 2D arena, returned by `setup_2d_arena`.
+///
+/// ### Type parameters:
+///
+/// - `Y`: Collider type
+#[derive(Debug, Clone)]
+pub struct Arena2<Y> {
+    walls: [Entity; 4],
+    types: [Y; 4],
+    min: Point2<f32>,
+    max: Point2<f32>,
+}
+
+impl<Y> Arena2<Y> {
+    /// Left wall
+    pub fn left(&self) -> Entity {
+        self.walls[0]
+    }
+
+    /// Right wall
+    pub fn right(&self) -> Entity {
+        self.walls[1]
+    }
+
+    /// Bottom wall
+    pub fn bottom(&self) -> Entity {
+        self.walls[2]
+    }
+
+    /// Top wall
+    pub fn top(&self) -> Entity {
+        self.walls[3]
+    }
+
+    /// All walls in order: Left, Right, Bottom, Top
+    pub fn walls(&self) -> &[Entity] {
+        &self.walls
+    }
+
+    /// Minimum corner of the arena, in render units
+    pub fn min(&self) -> Point2<f32> {
+        self.min
+    }
+
+    /// Maximum corner of the arena, in render units
+    pub fn max(&self) -> Point2<f32> {
+        self.max
+    }
+
+    /// Delete the walls of the arena
+    pub fn teardown(self, world: &mut World) {
+        delete_walls(&self.walls, world);
+    }
+}
+
+impl<Y> Arena2<Y>
+where
+    Y: Clone + Send + Sync + 'static,
+{
+    /// Move the walls to new corners, updating their shapes in place
+    ///
+    /// ### Parameters:
+    ///
+    /// - `min`: New minimum corner of the arena, in render units
+    /// - `max`: New maximum corner of the arena, in render units
+    /// - `world`: World
+    pub fn resize(&mut self, min: Point2<f32>, max: Point2<f32>, world: &mut World) {
+        let walls = walls_2d(&physics_units(world), min, max);
+        let mut poses = world.write_storage::<BodyPose2<f32>>();
+        let mut next_poses = world.write_storage::<NextFrame<BodyPose2<f32>>>();
+        let mut shapes = world.write_storage::<Shape2<Y>>();
+        for ((primitive, pose), (&entity, ty)) in
+            walls.iter().zip(self.walls.iter().zip(self.types.iter()))
+        {
+            if let Some(shape) = shapes.get_mut(entity) {
+                let enabled = shape.enabled;
+                *shape = wall_shape(primitive.clone(), ty.clone());
+                shape.enabled = enabled;
+                shape.update(pose, None);
+            }
+            if let Some(current) = poses.get_mut(entity) {
+                *current = pose.clone();
+            }
+            if let Some(next) = next_poses.get_mut(entity) {
+                next.value = pose.clone();
+            }
+        }
+        self.min = min;
+        self.max = max;
+    }
+}
+
+/// Handle to the walls of a 3D arena, returned by `setup_3d_arena`.
+///
+/// ### Type parameters:
+///
+/// - `Y`: Collider type
+#[derive(Debug, Clone)]
+pub struct Arena3<Y> {
+    walls: [Entity; 6],
+    types: [Y; 6],
+    min: Point3<f32>,
+    max: Point3<f32>,
+}
+
+impl<Y> Arena3<Y> {
+    /// Left wall
+    pub fn left(&self) -> Entity {
+        self.walls[0]
+    }
+
+    /// Right wall
+    pub fn right(&self) -> Entity {
+        self.walls[1]
+    }
+
+    /// Bottom wall
+    pub fn bottom(&self) -> Entity {
+        self.walls[2]
+    }
+
+    /// Top wall
+    pub fn top(&self) -> Entity {
+        self.walls[3]
+    }
+
+    /// Front wall
+    pub fn front(&self) -> Entity {
+        self.walls[4]
+    }
+
+    /// Back wall
+    pub fn back(&self) -> Entity {
+        self.walls[5]
+    }
+
+    /// All walls in order: Left, Right, Bottom, Top, Front, Back
+    pub fn walls(&self) -> &[Entity] {
+        &self.walls
+    }
+
+    /// Minimum corner of the arena, in render units
+    pub fn min(&self) -> Point3<f32> {
+        self.min
+    }
+
+    /// Maximum corner of the arena, in render units
+    pub fn max(&self) -> Point3<f32> {
+        self.max
+    }
+
+    /// Delete the walls of the arena
+    pub fn teardown(self, world: &mut World) {
+        delete_walls(&self.walls, world);
+    }
+}
+
+impl<Y> Arena3<Y>
+where
+    Y: Clone + Send + Sync + 'static,
+{
+    /// Move the walls to new corners, updating their shapes in place
+    ///
+    /// ### Parameters:
+    ///
+    /// - `min`: New minimum corner of the arena, in render units
+    /// - `max`: New maximum corner of the arena, in render units
+    /// - `world`: World
+    pub fn resize(&mut self, min: Point3<f32>, max: Point3<f32>, world: &mut World) {
+        let walls = walls_3d(&physics_units(world), min, max);
+        let mut poses = world.write_storage::<BodyPose3<f32>>();
+        let mut next_poses = world.write_storage::<NextFrame<BodyPose3<f32>>>();
+        let mut shapes = world.write_storage::<Shape3<Y>>();
+        for ((primitive, pose), (&entity, ty)) in
+            walls.iter().zip(self.walls.iter().zip(self.types.iter()))
+        {
+            if let Some(shape) = shapes.get_mut(entity) {
+                let enabled = shape.enabled;
+                *shape = wall_shape(primitive.clone(), ty.clone());
+                shape.enabled = enabled;
+                shape.update(pose, None);
+            }
+            if let Some(current) = poses.get_mut(entity) {
+                *current = pose.clone();
+            }
+            if let Some(next) = next_poses.get_mut(entity) {
+                next.value = pose.clone();
+            }
+        }
+        self.min = min;
+        self.max = max;
+    }
+}
+
 /// Setup 3D arena.
 ///
 /// An arena is a space with invisible walls around it, which have collision shapes defined.
 ///
+/// The corners are scaled to physics units by the `PhysicsUnits` resource, if there is one.
+/// Returns a handle to the walls, for moving or deleting them later.
+///
 /// ### Parameters:
 ///
-/// - `min`: Minimum corner of the arena
-/// - `max`: Maximum corner of the arena
+/// - `min`: Minimum corner of the arena, in render units
+/// - `max`: Maximum corner of the arena, in render units
 /// - `types`: Collider type of each arena barrier in order: Left, Right, Bottom, Top, Front, Back
 /// - `world`: World
 ///
@@ -26,159 +232,246 @@ pub fn setup_3d_arena<Y>(
     max: Point3<f32>,
     types: (Y, Y, Y, Y, Y, Y),
     world: &mut World,
-) where
-    Y: Default + Send + Sync + 'static,
+) -> Arena3<Y>
+where
+    Y: Default + Clone + Send + Sync + 'static,
 {
-    let dimension = max - min;
-    let center = (min + max.to_vec()) / 2.;
-    create_3d_wall(
-        world,
-        Vector2::new(dimension.z, dimension.y),
-        types.0,
-        Point3::new(min.x, center.y, center.z),
-        Quaternion::from_angle_y(Deg(90.)),
-    );
-    create_3d_wall(
-        world,
-        Vector2::new(dimension.z, dimension.y),
-        types.1,
-        Point3::new(max.x, center.y, center.z),
-        Quaternion::from_angle_y(Deg(90.)),
-    );
-    create_3d_wall(
-        world,
-        Vector2::new(dimension.x, dimension.z),
-        types.2,
-        Point3::new(center.x, min.y, center.z),
-        Quaternion::from_angle_x(Deg(90.)),
-    );
-    create_3d_wall(
-        world,
-        Vector2::new(dimension.x, dimension.z),
-        types.3,
-        Point3::new(center.x, max.y, center.z),
-        Quaternion::from_angle_x(Deg(90.)),
-    );
-    create_3d_wall(
-        world,
-        Vector2::new(dimension.x, dimension.y),
-        types.4,
-        Point3::new(center.x, center.y, max.z),
-        Quaternion::one(),
-    );
-    create_3d_wall(
-        world,
-        Vector2::new(dimension.x, dimension.y),
-        types.5,
-        Point3::new(center.x, center.y, min.z),
-        Quaternion::one(),
-    );
+    let types = [types.0, types.1, types.2, types.3, types.4, types.5];
+    let walls = walls_3d(&physics_units(world), min, max);
+    let mut wall = |index: usize| {
+        let (ref primitive, ref pose) = walls[index];
+        world
+            .create_entity()
+            .with_static_physical_entity(
+                wall_shape(primitive.clone(), types[index].clone()),
+                pose.clone(),
+                PhysicalEntity::default(),
+                Mass3::infinite(),
+            )
+            .build()
+    };
+    Arena3 {
+        walls: [wall(0), wall(1), wall(2), wall(3), wall(4), wall(5)],
+        types,
+        min,
+        max,
+    }
 }
 
 /// Setup 2D arena.
 ///
 /// An arena is a space with invisible walls around the space, that defines a collision room.
 ///
+/// The corners are scaled to physics units by the `PhysicsUnits` resource, if there is one.
+/// Returns a handle to the walls, for moving or deleting them later.
+///
 /// ### Parameters:
 ///
-/// - `min`: Minimum corner of the arena
-/// - `max`: Maximum corner of the arena
+/// - `min`: Minimum corner of the arena, in render units
+/// - `max`: Maximum corner of the arena, in render units
 /// - `types`: Collider type of each arena barrier in order: Left, Right, Bottom, Top
 /// - `world`: World
 ///
 /// ### Type parameters:
 ///
 /// - `Y`: Collider type
-pub fn setup_2d_arena<Y>(min: Point2<f32>, max: Point2<f32>, types: (Y, Y, Y, Y), world: &mut World)
+pub fn setup_2d_arena<Y>(
+    min: Point2<f32>,
+    max: Point2<f32>,
+    types: (Y, Y, Y, Y),
+    world: &mut World,
+) -> Arena2<Y>
 where
-    Y: Default + Send + Sync + 'static,
+    Y: Default + Clone + Send + Sync + 'static,
 {
-    type Shape2<Y> = CollisionShape<Primitive2<f32>, BodyPose2<f32>, Aabb2<f32>, Y>;
+    let types = [types.0, types.1, types.2, types.3];
+    let walls = walls_2d(&physics_units(world), min, max);
+    let mut wall = |index: usize| {
+        let (ref primitive, ref pose) = walls[index];
+        world
+            .create_entity()
+            .with_static_physical_entity(
+                wall_shape(primitive.clone(), types[index].clone()),
+                pose.clone(),
+                PhysicalEntity::default(),
+                Mass2::infinite(),
+            )
+            .build()
+    };
+    Arena2 {
+        walls: [wall(0), wall(1), wall(2), wall(3)],
+        types,
+        min,
+        max,
+    }
+}
+
+/// Shapes and poses of the walls of a 2D arena, in order: Left, Right, Bottom, Top
+fn walls_2d(
+    units: &PhysicsUnits,
+    min: Point2<f32>,
+    max: Point2<f32>,
+) -> [(Primitive2<f32>, BodyPose2<f32>); 4] {
+    let min = units.point_to_meters(&min);
+    let max = units.point_to_meters(&max);
     let dimensions = max - min;
     let center = (min + max.to_vec()) / 2.;
-    world
-        .create_entity()
-        .with_static_physical_entity(
-            Shape2::new_simple_with_type(
-                CollisionStrategy::FullResolution,
-                CollisionMode::Discrete,
-                Line2::new(Point2::new(0.0, -dimensions.y / 2.0), Point2::new(0.0, dimensions.y / 2.0)).into(),
-                types.0,
-            ),
+    let vertical = || -> Primitive2<f32> {
+        Line2::new(
+            Point2::new(0.0, -dimensions.y / 2.0),
+            Point2::new(0.0, dimensions.y / 2.0),
+        )
+        .into()
+    };
+    let horizontal = || -> Primitive2<f32> {
+        Line2::new(
+            Point2::new(-dimensions.x / 2.0, 0.0),
+            Point2::new(dimensions.x / 2.0, 0.0),
+        )
+        .into()
+    };
+    [
+        (
+            vertical(),
             BodyPose2::new(Point2::new(min.x, center.y), Basis2::one()),
-            PhysicalEntity::default(),
-            Mass2::infinite(),
-        ).build();
-
-    world
-        .create_entity()
-        .with_static_physical_entity(
-            Shape2::new_simple_with_type(
-                CollisionStrategy::FullResolution,
-                CollisionMode::Discrete,
-                Line2::new(Point2::new(0.0, -dimensions.y / 2.0), Point2::new(0.0, dimensions.y / 2.0)).into(),
-                types.1,
-            ),
+        ),
+        (
+            vertical(),
             BodyPose2::new(Point2::new(max.x, center.y), Basis2::one()),
-            PhysicalEntity::default(),
-            Mass2::infinite(),
-        ).build();
-
-    world
-        .create_entity()
-        .with_static_physical_entity(
-            Shape2::new_simple_with_type(
-                CollisionStrategy::FullResolution,
-                CollisionMode::Discrete,
-                Line2::new(Point2::new(-dimensions.x / 2.0, 0.0), Point2::new(dimensions.x / 2.0, 0.0)).into(),
-                types.2,
-            ),
+        ),
+        (
+            horizontal(),
             BodyPose2::new(Point2::new(center.x, min.y), Basis2::one()),
-            PhysicalEntity::default(),
-            Mass2::infinite(),
-        ).build();
-
-    world
-        .create_entity()
-        .with_static_physical_entity(
-            Shape2::new_simple_with_type(
-                CollisionStrategy::FullResolution,
-                CollisionMode::Discrete,
-                Line2::new(Point2::new(-dimensions.x / 2.0, 0.0), Point2::new(dimensions.x / 2.0, 0.0)).into(),
-                types.3,
-            ),
+        ),
+        (
+            horizontal(),
             BodyPose2::new(Point2::new(center.x, max.y), Basis2::one()),
-            PhysicalEntity::default(),
-            Mass2::infinite(),
-        ).build();
+        ),
+    ]
 }
 
-fn create_3d_wall<Y>(
-    world: &mut World,
-    dimension: Vector2<f32>,
-    t: Y,
-    position: Point3<f32>,
-    rot: Quaternion<f32>,
-) where
-    Y: Default + Send + Sync + 'static,
+/// Shapes and poses of the walls of a 3D arena, in order: Left, Right, Bottom, Top, Front, Back
+fn walls_3d(
+    units: &PhysicsUnits,
+    min: Point3<f32>,
+    max: Point3<f32>,
+) -> [(Primitive3<f32>, BodyPose3<f32>); 6] {
+    let min = units.point_to_meters(&min);
+    let max = units.point_to_meters(&max);
+    let dimension = max - min;
+    let center = (min + max.to_vec()) / 2.;
+    let wall = |dimension: Vector2<f32>, position: Point3<f32>, rot: Quaternion<f32>| {
+        let primitive: Primitive3<f32> = Quad::new_impl(dimension).into();
+        (primitive, BodyPose3::new(position, rot))
+    };
+    [
+        wall(
+            Vector2::new(dimension.z, dimension.y),
+            Point3::new(min.x, center.y, center.z),
+            Quaternion::from_angle_y(Deg(90.)),
+        ),
+        wall(
+            Vector2::new(dimension.z, dimension.y),
+            Point3::new(max.x, center.y, center.z),
+            Quaternion::from_angle_y(Deg(90.)),
+        ),
+        wall(
+            Vector2::new(dimension.x, dimension.z),
+            Point3::new(center.x, min.y, center.z),
+            Quaternion::from_angle_x(Deg(90.)),
+        ),
+        wall(
+            Vector2::new(dimension.x, dimension.z),
+            Point3::new(center.x, max.y, center.z),
+            Quaternion::from_angle_x(Deg(90.)),
+        ),
+        wall(
+            Vector2::new(dimension.x, dimension.y),
+            Point3::new(center.x, center.y, max.z),
+            Quaternion::one(),
+        ),
+        wall(
+            Vector2::new(dimension.x, dimension.y),
+            Point3::new(center.x, center.y, min.z),
+            Quaternion::one(),
+        ),
+    ]
+}
+
+fn wall_shape<P, T, B, Y>(primitive: P, ty: Y) -> CollisionShape<P, T, B, Y>
+where
+    P: Primitive + ComputeBound<B>,
+    B: Bound<Point = P::Point> + Union<B, Output = B> + Clone,
+    T: Transform<P::Point>,
 {
-    println!(
-        "Wall: {}, {}, {}, {}, {}",
-        dimension.x, dimension.y, position.x, position.y, position.z
-    );
+    CollisionShape::new_simple_with_type(
+        CollisionStrategy::FullResolution,
+        CollisionMode::Discrete,
+        primitive,
+        ty,
+    )
+}
+
+fn delete_walls(walls: &[Entity], world: &mut World) {
+    for &wall in walls {
+        // Walls already deleted some other way are skipped
+        let _ = world.delete_entity(wall);
+    }
+}
+
+fn physics_units(world: &World) -> PhysicsUnits {
     world
-        .create_entity()
-        .with_static_physical_entity(
-            Shape3::new_simple_with_type(
-                CollisionStrategy::FullResolution,
-                CollisionMode::Discrete,
-                Quad::new_impl(dimension).into(),
-                t,
-            ),
-            BodyPose3::new(position, rot),
-            PhysicalEntity::default(),
-            Mass3::infinite(),
-        ).build();
+        .try_fetch::<PhysicsUnits>()
+        .map(|units| *units)
+        .unwrap_or_default()
 }
 
-type Shape3<Y> = CollisionShape<Primitive3<f32>, BodyPose3<f32>, Aabb3<f32>, Y>;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.register::<BodyPose2<f32>>();
+        world.register::<NextFrame<BodyPose2<f32>>>();
+        world.register::<Shape2<()>>();
+        world.register::<PhysicalEntity<f32>>();
+        world.register::<Mass2<f32>>();
+        world
+    }
+
+    #[test]
+    fn test_resize_2d_arena() {
+        let mut world = world();
+        let mut arena = setup_2d_arena::<()>(
+            Point2::new(0., 0.),
+            Point2::new(10., 10.),
+            Default::default(),
+            &mut world,
+        );
+        arena.resize(Point2::new(0., 0.), Point2::new(20., 10.), &mut world);
+        assert_eq!(Point2::new(20., 10.), arena.max());
+
+        let poses = world.read_storage::<BodyPose2<f32>>();
+        assert_eq!(Point2::new(20., 5.), poses.get(arena.right()).unwrap().position());
+        let shapes = world.read_storage::<Shape2<()>>();
+        let bound = shapes.get(arena.bottom()).unwrap().bound();
+        assert_eq!(0., bound.min.x);
+        assert_eq!(20., bound.max.x);
+    }
+
+    #[test]
+    fn test_teardown_2d_arena() {
+        let mut world = world();
+        let arena = setup_2d_arena::<()>(
+            Point2::new(0., 0.),
+            Point2::new(10., 10.),
+            Default::default(),
+            &mut world,
+        );
+        let walls = arena.walls().to_vec();
+        arena.teardown(&mut world);
+        world.maintain();
+        assert!(walls.iter().all(|&wall| !world.is_alive(wall)));
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::{HashMap, HashSet};
+
+use cgmath::EuclideanSpace;
+use collision::{Bound, Discrete, HasBound};
+use rhusics_core::BroadPhase;
+use serde::{Deserialize, Serialize};
+
+/// Broad phase used by the physics bundles.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum BroadPhaseKind {
+    /// Sweep and prune, the default
+    SweepAndPrune,
+    /// Check all pairs of shapes, for scenes with few bodies
+    BruteForce,
+    /// Only use the dynamic bounding volume tree from spatial sorting, for large scenes.
+    ///
+    /// Enables spatial sorting.
+    Dbvt,
+    /// Uniform grid, for many bodies of similar size, see `SpatialHash`
+    SpatialHash {
+        /// Size of the grid cells, should be about the size of the common bodies. Must be
+        /// positive and finite.
+        cell_size: f32,
+    },
+}
+
+impl Default for BroadPhaseKind {
+    fn default() -> Self {
+        BroadPhaseKind::SweepAndPrune
+    }
+}
+
+/// Broad phase sorting the bounds of shapes into a uniform grid.
+///
+/// Shapes are added to every cell their bound overlaps, and shapes that share a cell are
+/// potential collisions if their bounds intersect. Works well for large numbers of bodies of
+/// about the size of a cell, while large shapes that cover many cells are expensive.
+#[derive(Debug, Clone)]
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<[i32; 3], Vec<usize>>,
+}
+
+impl SpatialHash {
+    /// Create new spatial hash
+    ///
+    /// ### Parameters:
+    ///
+    /// - `cell_size`: Size of the grid cells
+    ///
+    /// Panics if `cell_size` isn't positive and finite.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(
+            cell_size.is_finite() && cell_size > 0.,
+            "spatial hash cell size must be positive and finite, got {}",
+            cell_size
+        );
+        Self {
+            cell_size,
+            cells: HashMap::default(),
+        }
+    }
+
+    /// Size of the grid cells
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    fn cell<P>(&self, point: &P) -> [i32; 3]
+    where
+        P: EuclideanSpace<Scalar = f32>,
+    {
+        let mut cell = [0; 3];
+        for (axis, value) in cell.iter_mut().enumerate().take(P::len()) {
+            *value = (point[axis] / self.cell_size).floor() as i32;
+        }
+        cell
+    }
+}
+
+impl<D> BroadPhase<D> for SpatialHash
+where
+    D: HasBound,
+    D::Bound: Bound + Discrete<D::Bound>,
+    <D::Bound as Bound>::Point: EuclideanSpace<Scalar = f32>,
+{
+    fn find_potentials(&mut self, shapes: &mut [D]) -> Vec<(usize, usize)> {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+        for (index, shape) in shapes.iter().enumerate() {
+            let min = self.cell(&shape.bound().min_extent());
+            let max = self.cell(&shape.bound().max_extent());
+            for x in min[0]..=max[0] {
+                for y in min[1]..=max[1] {
+                    for z in min[2]..=max[2] {
+                        self.cells.entry([x, y, z]).or_default().push(index);
+                    }
+                }
+            }
+        }
+        // Drop cells that stayed empty, so the map doesn't grow without bounds
+        self.cells.retain(|_, cell| !cell.is_empty());
+
+        let mut pairs = HashSet::new();
+        for cell in self.cells.values() {
+            for (i, &left) in cell.iter().enumerate() {
+                for &right in &cell[i + 1..] {
+                    if shapes[left].bound().intersects(shapes[right].bound()) {
+                        pairs.insert((left, right));
+                    }
+                }
+            }
+        }
+        let mut pairs = pairs.into_iter().collect::<Vec<_>>();
+        pairs.sort();
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Point2;
+    use collision::Aabb2;
+
+    use super::*;
+
+    struct Shape(Aabb2<f32>);
+
+    impl HasBound for Shape {
+        type Bound = Aabb2<f32>;
+
+        fn bound(&self) -> &Aabb2<f32> {
+            &self.0
+        }
+    }
+
+    fn shape(x: f32, y: f32, size: f32) -> Shape {
+        Shape(Aabb2::new(
+            Point2::new(x, y),
+            Point2::new(x + size, y + size),
+        ))
+    }
+
+    #[test]
+    fn test_spatial_hash_potentials() {
+        let mut shapes = vec![
+            shape(0., 0., 1.),
+            shape(0.5, 0.5, 1.),
+            shape(5., 5., 1.),
+            // Spans many cells, and overlaps both of the first shapes in several of them
+            shape(-2., -2., 4.),
+        ];
+        let mut broad = SpatialHash::new(1.);
+        assert_eq!(
+            vec![(0, 1), (0, 3), (1, 3)],
+            broad.find_potentials(&mut shapes)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_spatial_hash_nan_cell_size() {
+        SpatialHash::new(std::f32::NAN);
+    }
+}
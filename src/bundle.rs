@@ -1,19 +1,52 @@
+use std::any::type_name;
 use std::fmt::Debug;
 use std::marker;
+use std::path::{Path, PathBuf};
 
-use amethyst_core::ecs::{DispatcherBuilder, Entity, World};
+use amethyst_config::Config;
+use amethyst_core::ecs::{Component, DispatcherBuilder, Entity, System, World, WorldExt};
 use amethyst_error::Error;
 use amethyst_core::{SystemBundle};
-use cgmath::{Basis2, Point2, Point3, Quaternion};
-use collision::algorithm::broad_phase::{SweepAndPrune2, SweepAndPrune3};
+use cgmath::{Basis2, Point2, Point3, Quaternion, Vector2, Vector3};
+use collision::algorithm::broad_phase::{BruteForce, SweepAndPrune2, SweepAndPrune3};
 use collision::dbvt::TreeValueWrapped;
 use collision::{Bound, ComputeBound, Contains, Discrete, Primitive, SurfaceArea, Union};
-use rhusics_core::{BodyPose, Collider};
-use rhusics_ecs::physics2d::{setup_dispatch_2d, GJK2};
-use rhusics_ecs::physics3d::{setup_dispatch_3d, GJK3};
-use rhusics_ecs::DeltaTime;
+use rhusics_core::{
+    BodyPose, BroadPhase, Collider, CollisionShape, ContactEvent, NextFrame, PhysicalEntity,
+    WorldParameters,
+};
+use rhusics_ecs::physics2d::{
+    ContactResolutionSystem2, CurrentFrameUpdateSystem2, ForceAccumulator2, Mass2,
+    NextFrameSetupSystem2, Velocity2, GJK2,
+};
+use rhusics_ecs::physics3d::{
+    ContactResolutionSystem3, CurrentFrameUpdateSystem3, ForceAccumulator3, Mass3,
+    NextFrameSetupSystem3, Velocity3, GJK3,
+};
+use rhusics_ecs::{BasicCollisionSystem, DeltaTime, SpatialCollisionSystem, SpatialSortingSystem};
+use shrev::EventChannel;
 
-use crate::default::{PoseTransformSyncSystem2, PoseTransformSyncSystem3};
+use crate::broad::{BroadPhaseKind, SpatialHash};
+use crate::config::{NarrowPhaseConfig, PhysicsConfig, PhysicsConfigReloadSystem, WorldConfig};
+use crate::contacts::{CollisionEvent, CollisionTrackingSystem, Contacts, ContactsSystem};
+use crate::default::{
+    ImpactSystem2, ImpactSystem3, PoseTransformSyncSystem2, PoseTransformSyncSystem3,
+    TransformPoseSyncSystem2, TransformPoseSyncSystem3,
+};
+use crate::guard::SkipOnZeroDelta;
+use crate::impact::ImpactEvent;
+use crate::plane::Plane2Mapping;
+use crate::route::ContactRouteSystem;
+use crate::scale::ScaledShape;
+use crate::sensor::{Sensor, SensorEvent, SensorEventSystem};
+use crate::stage::{as_strs, PhysicsStage, PhysicsSystemNames, StagedSystems};
+use crate::step::{
+    physics_dispatcher_builder, FixedStepPhysics, FixedStepPhysicsSystem, PhysicsTimeScale,
+};
+use crate::sync::{SyncDirection, SyncMask, VisualOffset};
+
+/// Contact route waiting for the system names, see `route_contacts`.
+type AddRoute = Box<dyn FnOnce(&mut World, &PhysicsSystemNames, &mut StagedSystems)>;
 
 /// Bundle for configuring 2D physics.
 ///
@@ -25,6 +58,19 @@ use crate::default::{PoseTransformSyncSystem2, PoseTransformSyncSystem3};
 pub struct PhysicsBundle2<P, B, Y> {
     m: marker::PhantomData<(P, B, Y)>,
     spatial: bool,
+    broad_phase: BroadPhaseKind,
+    narrow_phase: NarrowPhaseConfig,
+    fixed_step: Option<FixedStepPhysics>,
+    collision_events: bool,
+    contacts: bool,
+    impacts: bool,
+    world: Option<WorldConfig>,
+    config_path: Option<PathBuf>,
+    plane: Plane2Mapping,
+    names: PhysicsSystemNames,
+    dependencies: Vec<String>,
+    systems: StagedSystems,
+    routes: Vec<AddRoute>,
 }
 
 impl<P, B, Y> PhysicsBundle2<P, B, Y> {
@@ -33,6 +79,19 @@ impl<P, B, Y> PhysicsBundle2<P, B, Y> {
         Self {
             m: marker::PhantomData,
             spatial: false,
+            broad_phase: BroadPhaseKind::default(),
+            narrow_phase: NarrowPhaseConfig::default(),
+            fixed_step: None,
+            collision_events: false,
+            contacts: false,
+            impacts: false,
+            world: None,
+            config_path: None,
+            plane: Plane2Mapping::default(),
+            names: PhysicsSystemNames::default(),
+            dependencies: Vec::new(),
+            systems: StagedSystems::new(),
+            routes: Vec::new(),
         }
     }
 
@@ -44,6 +103,179 @@ impl<P, B, Y> PhysicsBundle2<P, B, Y> {
         self.spatial = true;
         self
     }
+
+    /// Select the broad phase
+    ///
+    /// `BroadPhaseKind::SweepAndPrune` is the default. All broad phases work with or without
+    /// spatial sorting, except `BroadPhaseKind::Dbvt`, which always enables it.
+    pub fn with_broad_phase(mut self, broad_phase: BroadPhaseKind) -> Self {
+        self.broad_phase = broad_phase;
+        self
+    }
+
+    /// Run physics with a fixed time step
+    ///
+    /// The physics systems are moved into a `FixedStepPhysicsSystem`, which runs them
+    /// `step` seconds at a time, at most `max_substeps` times per frame. `time_sync` should not
+    /// be used with this, and the sync system interpolates between physics steps, running as a
+    /// thread local system after it, along with the `PhysicsStage::PostSync` systems.
+    ///
+    /// Panics if `step` isn't positive and finite, or if `max_substeps` is zero.
+    pub fn with_fixed_step(mut self, step: f32, max_substeps: u32) -> Self {
+        self.fixed_step = Some(FixedStepPhysics::new(step, max_substeps));
+        self
+    }
+
+    /// Send `CollisionEvent`s telling new contacts apart from ongoing ones
+    ///
+    /// Adds a `CollisionTrackingSystem` right after collision detection, and a
+    /// `SensorEventSystem` sending `SensorEvent`s for the collisions with a `Sensor`.
+    pub fn with_collision_events(mut self) -> Self {
+        self.collision_events = true;
+        self
+    }
+
+    /// Fill the `Contacts` components after each physics step
+    ///
+    /// Adds a `ContactsSystem` right after collision detection, which clears all `Contacts`
+    /// components and reads all contacts every step.
+    pub fn with_contacts(mut self) -> Self {
+        self.contacts = true;
+        self
+    }
+
+    /// Send an `ImpactEvent` for each pair of colliding bodies
+    ///
+    /// Adds an `ImpactSystem` right after collision detection, computing the impulse and energy
+    /// loss of each impact before the contact is resolved.
+    pub fn with_impacts(mut self) -> Self {
+        self.impacts = true;
+        self
+    }
+
+    /// Send the contacts between bodies matching a pair of filters to their own channel
+    ///
+    /// Adds a `ContactRouteSystem`, writing `RoutedContact<R, _>`s for the contacts where one
+    /// body's `Y` component matches `first` and the other one's matches `second`, with the body
+    /// matching `first` always first. The collider type inside the `CollisionShape` can't be
+    /// read, so the bodies need a copy of it as a component, which the bundle registers. The
+    /// system is named after the collision system and `R`.
+    ///
+    /// ### Type parameters:
+    ///
+    /// - `R`: Tag type of the route, telling the channels of different routes apart
+    pub fn route_contacts<R, F, G>(mut self, first: F, second: G) -> Self
+    where
+        R: 'static,
+        Y: Component,
+        Y::Storage: Default,
+        F: Fn(&Y) -> bool + Send + Sync + 'static,
+        G: Fn(&Y) -> bool + Send + Sync + 'static,
+    {
+        self.routes.push(Box::new(move |world, names, systems| {
+            world.register::<Y>();
+            let name = format!("{}_route_{}", names.collision, type_name::<R>());
+            systems.push(
+                PhysicsStage::PostContact,
+                ContactRouteSystem::<R, Point2<f32>, Y>::new(first, second),
+                &name,
+                &[],
+            );
+        }));
+        self
+    }
+
+    /// Apply physics settings
+    ///
+    /// Replaces the settings of `with_spatial`, `with_broad_phase` and `with_fixed_step` that
+    /// are set in the config, and the narrow phase settings. With world settings in the config,
+    /// `WorldParameters` with its gravity and damping are inserted, unless the world already has
+    /// them.
+    ///
+    /// Panics, here or when building, if the config fails `PhysicsConfig::validate`.
+    pub fn with_config(mut self, config: PhysicsConfig) -> Self {
+        let step = config.step;
+        if let Some(spatial) = config.spatial {
+            self.spatial = spatial;
+        }
+        if let Some(broad_phase) = config.broad_phase {
+            self.broad_phase = broad_phase;
+        }
+        self.narrow_phase = config.narrow_phase;
+        if let Some(fixed_step) = step.fixed_step {
+            self.fixed_step = Some(FixedStepPhysics::new(fixed_step, step.max_substeps));
+        }
+        if config.world.is_some() {
+            self.world = config.world;
+        }
+        self
+    }
+
+    /// Create new bundle with the physics settings in a RON file, see `PhysicsConfig`
+    ///
+    /// Adds a `PhysicsConfigReloadSystem`, so changes to the gravity, damping and fixed time
+    /// step in the file apply while the game is running. Fails if the file can't be loaded, or
+    /// if the settings don't pass `PhysicsConfig::validate`.
+    pub fn from_config_path<T: AsRef<Path>>(path: T) -> Result<Self, Error> {
+        let config = PhysicsConfig::load(path.as_ref())?;
+        config.validate()?;
+        let mut bundle = Self::new().with_config(config);
+        bundle.config_path = Some(path.as_ref().to_path_buf());
+        Ok(bundle)
+    }
+
+    /// Map the 2D physics onto the given plane, instead of the XY plane
+    ///
+    /// Used by both sync systems, for example `Plane2Mapping::XZ` for physics on the ground of a
+    /// 3D world with Y up.
+    pub fn with_plane(mut self, plane: Plane2Mapping) -> Self {
+        self.plane = plane;
+        self
+    }
+
+    /// Prefix the names of all systems added by the bundle
+    ///
+    /// Needed when adding more than one physics bundle to a dispatcher.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.names = PhysicsSystemNames::with_prefix(prefix);
+        self
+    }
+
+    /// Make the first systems added by the bundle depend on the given systems
+    ///
+    /// These are the transform sync system, and the physics solver system unless running with a
+    /// fixed time step, as the physics systems and the sync system then run as thread local
+    /// systems after all others.
+    pub fn with_dependencies(mut self, dependencies: &[&str]) -> Self {
+        self.dependencies.extend(dependencies.iter().map(|dependency| dependency.to_string()));
+        self
+    }
+
+    /// Add a system at the given stage of the physics systems
+    ///
+    /// The system depends on the systems of the previous stage, as well as `dependencies`, and
+    /// the systems of the next stage depend on it.
+    pub fn with_system_at<S>(
+        mut self,
+        stage: PhysicsStage,
+        system: S,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'static,
+    {
+        self.systems.push(stage, system, name, dependencies);
+        self
+    }
+
+    /// Names of the systems added by the bundle
+    ///
+    /// With `with_fixed_step`, most of these systems aren't in the main dispatcher and can't be
+    /// depended on, see `PhysicsSystemNames`.
+    pub fn system_names(&self) -> &PhysicsSystemNames {
+        &self.names
+    }
 }
 
 impl<'a, 'b, P, B, Y> SystemBundle<'a, 'b> for PhysicsBundle2<P, B, Y>
@@ -61,32 +293,288 @@ where
         + 'static,
     Y: Default + Collider + Send + Sync + 'static,
 {
-    fn build(self, _world: &mut World, dispatcher: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
-        setup_dispatch_2d::<
-            f32,
-            P,
-            BodyPose<Point2<f32>, Basis2<f32>>,
-            B,
-            TreeValueWrapped<Entity, B>,
-            Y,
-            _,
-            _,
-            DeltaTime<f32>,
-        >(
-            dispatcher,
-            SweepAndPrune2::<f32, B>::new(),
-            GJK2::new(),
-            self.spatial,
-        );
+    fn build(mut self, world: &mut World, dispatcher: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+        // Before registering, which would insert the default parameters
+        if let Some(ref config) = self.world {
+            world
+                .entry::<WorldParameters<Vector2<f32>, f32>>()
+                .or_insert_with(|| config.parameters());
+        }
+        register_2d::<P, B, Y>(world);
+        world
+            .entry::<PhysicsTimeScale>()
+            .or_insert_with(PhysicsTimeScale::default);
+        let names = self.names;
+        let dependencies = as_strs(&self.dependencies);
+        for route in self.routes.drain(..) {
+            route(world, &names, &mut self.systems);
+        }
+        if self.contacts {
+            self.systems.push(
+                PhysicsStage::PostContact,
+                SkipOnZeroDelta::new(ContactsSystem::<Point2<f32>>::new()),
+                &names.contacts,
+                &[],
+            );
+        }
+        if self.impacts {
+            self.systems.push(
+                PhysicsStage::PostContact,
+                SkipOnZeroDelta::new(ImpactSystem2::new()),
+                &names.impacts,
+                &[],
+            );
+        }
+        if self.collision_events {
+            self.systems.push(
+                PhysicsStage::PostContact,
+                SkipOnZeroDelta::new(CollisionTrackingSystem::<Point2<f32>>::new()),
+                &names.collision_tracking,
+                &[],
+            );
+            self.systems.push(
+                PhysicsStage::PostContact,
+                SensorEventSystem::<Point2<f32>>::new(),
+                &names.sensor_events,
+                &[&names.collision_tracking],
+            );
+        }
+        if let Some(ref path) = self.config_path {
+            dispatcher.add(
+                PhysicsConfigReloadSystem::<Vector2<f32>>::new(path),
+                &names.config_reload,
+                &dependencies,
+            );
+        }
+        // Added before the physics systems, so kinematic poses are in place when they run
         dispatcher.add(
-            PoseTransformSyncSystem2::new(),
-            "sync_system",
-            &["physics_solver_system"],
+            TransformPoseSyncSystem2::new().with_plane(self.plane),
+            &names.transform_sync,
+            &dependencies,
         );
+        match self.fixed_step {
+            Some(fixed_step) => {
+                world.insert(fixed_step);
+                let mut physics = physics_dispatcher_builder(world);
+                setup_physics_2d::<P, B, Y>(
+                    &mut physics,
+                    self.spatial,
+                    self.broad_phase,
+                    &self.narrow_phase,
+                    &names,
+                    &[],
+                    &mut self.systems,
+                );
+                dispatcher.add_thread_local(FixedStepPhysicsSystem::new(physics.build()));
+                // Thread local, so the sync runs after the physics steps of this frame
+                dispatcher.add_thread_local(
+                    PoseTransformSyncSystem2::new()
+                        .with_interpolation()
+                        .with_plane(self.plane),
+                );
+                self.systems
+                    .add_stage_thread_local(PhysicsStage::PostSync, dispatcher);
+            }
+            None => {
+                setup_physics_2d::<P, B, Y>(
+                    dispatcher,
+                    self.spatial,
+                    self.broad_phase,
+                    &self.narrow_phase,
+                    &names,
+                    &dependencies,
+                    &mut self.systems,
+                );
+                dispatcher.add(
+                    PoseTransformSyncSystem2::new().with_plane(self.plane),
+                    &names.sync,
+                    &[&names.solver],
+                );
+                self.systems
+                    .add_stage(PhysicsStage::PostSync, dispatcher, &[&names.sync]);
+            }
+        }
         Ok(())
     }
 }
 
+/// Register the components and insert the resources used by the 2D physics systems, so they
+/// exist even when no system fetches them before the entities are created.
+fn register_2d<P, B, Y>(world: &mut World)
+where
+    P: Primitive<Point = Point2<f32>> + Send + Sync + 'static,
+    B: Bound<Point = P::Point> + Send + Sync + 'static,
+    Y: Send + Sync + 'static,
+{
+    type T = BodyPose<Point2<f32>, Basis2<f32>>;
+    world.register::<T>();
+    world.register::<NextFrame<T>>();
+    world.register::<Velocity2<f32>>();
+    world.register::<NextFrame<Velocity2<f32>>>();
+    world.register::<Mass2<f32>>();
+    world.register::<ForceAccumulator2<f32>>();
+    world.register::<PhysicalEntity<f32>>();
+    world.register::<CollisionShape<P, T, B, Y>>();
+    world.register::<ScaledShape<P, Y>>();
+    world.register::<Sensor>();
+    world.register::<Contacts<Point2<f32>>>();
+    world.register::<SyncDirection>();
+    world.register::<SyncMask>();
+    world.register::<VisualOffset>();
+    world
+        .entry::<WorldParameters<Vector2<f32>, f32>>()
+        .or_insert_with(WorldParameters::default);
+    world
+        .entry::<DeltaTime<f32>>()
+        .or_insert_with(DeltaTime::default);
+    world
+        .entry::<EventChannel<ContactEvent<Entity, Point2<f32>>>>()
+        .or_insert_with(EventChannel::default);
+    world
+        .entry::<EventChannel<CollisionEvent<Point2<f32>>>>()
+        .or_insert_with(EventChannel::default);
+    world
+        .entry::<EventChannel<ImpactEvent<Point2<f32>>>>()
+        .or_insert_with(EventChannel::default);
+    world
+        .entry::<EventChannel<SensorEvent>>()
+        .or_insert_with(EventChannel::default);
+}
+
+fn setup_physics_2d<P, B, Y>(
+    dispatcher: &mut DispatcherBuilder<'_, '_>,
+    spatial: bool,
+    broad_phase: BroadPhaseKind,
+    narrow_phase: &NarrowPhaseConfig,
+    names: &PhysicsSystemNames,
+    dependencies: &[&str],
+    systems: &mut StagedSystems,
+) where
+    P: Primitive<Point = Point2<f32>> + ComputeBound<B> + Send + Sync + 'static,
+    B: Bound<Point = P::Point>
+        + Clone
+        + Discrete<B>
+        + Union<B, Output = B>
+        + Contains<B>
+        + SurfaceArea<Scalar = f32>
+        + Debug
+        + Send
+        + Sync
+        + 'static,
+    Y: Default + Collider + Send + Sync + 'static,
+{
+    type T = BodyPose<Point2<f32>, Basis2<f32>>;
+    dispatcher.add(
+        CurrentFrameUpdateSystem2::<f32, T>::new(),
+        &names.solver,
+        dependencies,
+    );
+    dispatcher.add(
+        SkipOnZeroDelta::new(NextFrameSetupSystem2::<f32, T>::new()),
+        &names.next_frame_setup,
+        &[&names.solver],
+    );
+    let after = systems.add_stage(
+        PhysicsStage::PreBroadPhase,
+        dispatcher,
+        &[&names.next_frame_setup],
+    );
+    let spatial = spatial || broad_phase == BroadPhaseKind::Dbvt;
+    let after = if spatial {
+        dispatcher.add(
+            SpatialSortingSystem::<P, T, TreeValueWrapped<Entity, B>, B, Y>::new(),
+            &names.spatial_sorting,
+            &as_strs(&after),
+        );
+        vec![names.spatial_sorting.clone()]
+    } else {
+        after
+    };
+    let after = as_strs(&after);
+    match broad_phase {
+        BroadPhaseKind::SweepAndPrune => add_collision_system_2d::<P, B, Y, _>(
+            dispatcher,
+            spatial,
+            narrow_phase.gjk2(),
+            Some(SweepAndPrune2::<f32, B>::new()),
+            &names.collision,
+            &after,
+        ),
+        BroadPhaseKind::BruteForce => add_collision_system_2d::<P, B, Y, _>(
+            dispatcher,
+            spatial,
+            narrow_phase.gjk2(),
+            Some(BruteForce::default()),
+            &names.collision,
+            &after,
+        ),
+        // Spatial collision detection falls back to the tree without a broad phase
+        BroadPhaseKind::Dbvt => add_collision_system_2d::<P, B, Y, BruteForce>(
+            dispatcher,
+            spatial,
+            narrow_phase.gjk2(),
+            None,
+            &names.collision,
+            &after,
+        ),
+        BroadPhaseKind::SpatialHash { cell_size } => add_collision_system_2d::<P, B, Y, _>(
+            dispatcher,
+            spatial,
+            narrow_phase.gjk2(),
+            Some(SpatialHash::new(cell_size)),
+            &names.collision,
+            &after,
+        ),
+    }
+    let after = systems.add_stage(PhysicsStage::PostContact, dispatcher, &[&names.collision]);
+    dispatcher.add(
+        SkipOnZeroDelta::new(ContactResolutionSystem2::<f32, T>::new()),
+        &names.contact_resolution,
+        &as_strs(&after),
+    );
+    systems.add_stage(PhysicsStage::PostSolve, dispatcher, &[&names.contact_resolution]);
+}
+
+fn add_collision_system_2d<P, B, Y, V>(
+    dispatcher: &mut DispatcherBuilder<'_, '_>,
+    spatial: bool,
+    narrow_phase: GJK2<f32>,
+    broad_phase: Option<V>,
+    name: &str,
+    dependencies: &[&str],
+) where
+    P: Primitive<Point = Point2<f32>> + ComputeBound<B> + Send + Sync + 'static,
+    B: Bound<Point = P::Point>
+        + Clone
+        + Discrete<B>
+        + Union<B, Output = B>
+        + Contains<B>
+        + SurfaceArea<Scalar = f32>
+        + Debug
+        + Send
+        + Sync
+        + 'static,
+    Y: Default + Collider + Send + Sync + 'static,
+    V: BroadPhase<TreeValueWrapped<Entity, B>> + 'static,
+{
+    type T = BodyPose<Point2<f32>, Basis2<f32>>;
+    if spatial {
+        let mut system = SpatialCollisionSystem::<P, T, TreeValueWrapped<Entity, B>, B, Y>::new()
+            .with_narrow_phase(narrow_phase);
+        if let Some(broad_phase) = broad_phase {
+            system = system.with_broad_phase(broad_phase);
+        }
+        dispatcher.add(SkipOnZeroDelta::new(system), name, dependencies);
+    } else {
+        let mut system = BasicCollisionSystem::<P, T, TreeValueWrapped<Entity, B>, B, Y>::new()
+            .with_narrow_phase(narrow_phase);
+        if let Some(broad_phase) = broad_phase {
+            system = system.with_broad_phase(broad_phase);
+        }
+        dispatcher.add(SkipOnZeroDelta::new(system), name, dependencies);
+    }
+}
+
 /// Bundle for configuring 3D physics, using the basic collision detection setup in rhusics.
 ///
 /// ### Type parameters:
@@ -97,6 +585,18 @@ where
 pub struct PhysicsBundle3<P, B, Y> {
     m: marker::PhantomData<(P, B, Y)>,
     spatial: bool,
+    broad_phase: BroadPhaseKind,
+    narrow_phase: NarrowPhaseConfig,
+    fixed_step: Option<FixedStepPhysics>,
+    collision_events: bool,
+    contacts: bool,
+    impacts: bool,
+    world: Option<WorldConfig>,
+    config_path: Option<PathBuf>,
+    names: PhysicsSystemNames,
+    dependencies: Vec<String>,
+    systems: StagedSystems,
+    routes: Vec<AddRoute>,
 }
 
 impl<P, B, Y> PhysicsBundle3<P, B, Y> {
@@ -105,6 +605,18 @@ impl<P, B, Y> PhysicsBundle3<P, B, Y> {
         Self {
             m: marker::PhantomData,
             spatial: false,
+            broad_phase: BroadPhaseKind::default(),
+            narrow_phase: NarrowPhaseConfig::default(),
+            fixed_step: None,
+            collision_events: false,
+            contacts: false,
+            impacts: false,
+            world: None,
+            config_path: None,
+            names: PhysicsSystemNames::default(),
+            dependencies: Vec::new(),
+            systems: StagedSystems::new(),
+            routes: Vec::new(),
         }
     }
 
@@ -116,6 +628,170 @@ impl<P, B, Y> PhysicsBundle3<P, B, Y> {
         self.spatial = true;
         self
     }
+
+    /// Select the broad phase
+    ///
+    /// `BroadPhaseKind::SweepAndPrune` is the default. All broad phases work with or without
+    /// spatial sorting, except `BroadPhaseKind::Dbvt`, which always enables it.
+    pub fn with_broad_phase(mut self, broad_phase: BroadPhaseKind) -> Self {
+        self.broad_phase = broad_phase;
+        self
+    }
+
+    /// Run physics with a fixed time step
+    ///
+    /// The physics systems are moved into a `FixedStepPhysicsSystem`, which runs them
+    /// `step` seconds at a time, at most `max_substeps` times per frame. `time_sync` should not
+    /// be used with this, and the sync system interpolates between physics steps, running as a
+    /// thread local system after it, along with the `PhysicsStage::PostSync` systems.
+    ///
+    /// Panics if `step` isn't positive and finite, or if `max_substeps` is zero.
+    pub fn with_fixed_step(mut self, step: f32, max_substeps: u32) -> Self {
+        self.fixed_step = Some(FixedStepPhysics::new(step, max_substeps));
+        self
+    }
+
+    /// Send `CollisionEvent`s telling new contacts apart from ongoing ones
+    ///
+    /// Adds a `CollisionTrackingSystem` right after collision detection, and a
+    /// `SensorEventSystem` sending `SensorEvent`s for the collisions with a `Sensor`.
+    pub fn with_collision_events(mut self) -> Self {
+        self.collision_events = true;
+        self
+    }
+
+    /// Fill the `Contacts` components after each physics step
+    ///
+    /// Adds a `ContactsSystem` right after collision detection, which clears all `Contacts`
+    /// components and reads all contacts every step.
+    pub fn with_contacts(mut self) -> Self {
+        self.contacts = true;
+        self
+    }
+
+    /// Send an `ImpactEvent` for each pair of colliding bodies
+    ///
+    /// Adds an `ImpactSystem` right after collision detection, computing the impulse and energy
+    /// loss of each impact before the contact is resolved.
+    pub fn with_impacts(mut self) -> Self {
+        self.impacts = true;
+        self
+    }
+
+    /// Send the contacts between bodies matching a pair of filters to their own channel
+    ///
+    /// Adds a `ContactRouteSystem`, writing `RoutedContact<R, _>`s for the contacts where one
+    /// body's `Y` component matches `first` and the other one's matches `second`, with the body
+    /// matching `first` always first. The collider type inside the `CollisionShape` can't be
+    /// read, so the bodies need a copy of it as a component, which the bundle registers. The
+    /// system is named after the collision system and `R`.
+    ///
+    /// ### Type parameters:
+    ///
+    /// - `R`: Tag type of the route, telling the channels of different routes apart
+    pub fn route_contacts<R, F, G>(mut self, first: F, second: G) -> Self
+    where
+        R: 'static,
+        Y: Component,
+        Y::Storage: Default,
+        F: Fn(&Y) -> bool + Send + Sync + 'static,
+        G: Fn(&Y) -> bool + Send + Sync + 'static,
+    {
+        self.routes.push(Box::new(move |world, names, systems| {
+            world.register::<Y>();
+            let name = format!("{}_route_{}", names.collision, type_name::<R>());
+            systems.push(
+                PhysicsStage::PostContact,
+                ContactRouteSystem::<R, Point3<f32>, Y>::new(first, second),
+                &name,
+                &[],
+            );
+        }));
+        self
+    }
+
+    /// Apply physics settings
+    ///
+    /// Replaces the settings of `with_spatial`, `with_broad_phase` and `with_fixed_step` that
+    /// are set in the config, and the narrow phase settings. With world settings in the config,
+    /// `WorldParameters` with its gravity and damping are inserted, unless the world already has
+    /// them.
+    ///
+    /// Panics, here or when building, if the config fails `PhysicsConfig::validate`.
+    pub fn with_config(mut self, config: PhysicsConfig) -> Self {
+        let step = config.step;
+        if let Some(spatial) = config.spatial {
+            self.spatial = spatial;
+        }
+        if let Some(broad_phase) = config.broad_phase {
+            self.broad_phase = broad_phase;
+        }
+        self.narrow_phase = config.narrow_phase;
+        if let Some(fixed_step) = step.fixed_step {
+            self.fixed_step = Some(FixedStepPhysics::new(fixed_step, step.max_substeps));
+        }
+        if config.world.is_some() {
+            self.world = config.world;
+        }
+        self
+    }
+
+    /// Create new bundle with the physics settings in a RON file, see `PhysicsConfig`
+    ///
+    /// Adds a `PhysicsConfigReloadSystem`, so changes to the gravity, damping and fixed time
+    /// step in the file apply while the game is running. Fails if the file can't be loaded, or
+    /// if the settings don't pass `PhysicsConfig::validate`.
+    pub fn from_config_path<T: AsRef<Path>>(path: T) -> Result<Self, Error> {
+        let config = PhysicsConfig::load(path.as_ref())?;
+        config.validate()?;
+        let mut bundle = Self::new().with_config(config);
+        bundle.config_path = Some(path.as_ref().to_path_buf());
+        Ok(bundle)
+    }
+
+    /// Prefix the names of all systems added by the bundle
+    ///
+    /// Needed when adding more than one physics bundle to a dispatcher.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.names = PhysicsSystemNames::with_prefix(prefix);
+        self
+    }
+
+    /// Make the first systems added by the bundle depend on the given systems
+    ///
+    /// These are the transform sync system, and the physics solver system unless running with a
+    /// fixed time step, as the physics systems and the sync system then run as thread local
+    /// systems after all others.
+    pub fn with_dependencies(mut self, dependencies: &[&str]) -> Self {
+        self.dependencies.extend(dependencies.iter().map(|dependency| dependency.to_string()));
+        self
+    }
+
+    /// Add a system at the given stage of the physics systems
+    ///
+    /// The system depends on the systems of the previous stage, as well as `dependencies`, and
+    /// the systems of the next stage depend on it.
+    pub fn with_system_at<S>(
+        mut self,
+        stage: PhysicsStage,
+        system: S,
+        name: &str,
+        dependencies: &[&str],
+    ) -> Self
+    where
+        S: for<'c> System<'c> + Send + 'static,
+    {
+        self.systems.push(stage, system, name, dependencies);
+        self
+    }
+
+    /// Names of the systems added by the bundle
+    ///
+    /// With `with_fixed_step`, most of these systems aren't in the main dispatcher and can't be
+    /// depended on, see `PhysicsSystemNames`.
+    pub fn system_names(&self) -> &PhysicsSystemNames {
+        &self.names
+    }
 }
 
 impl<'a, 'b, P, B, Y> SystemBundle<'a, 'b> for PhysicsBundle3<P, B, Y>
@@ -133,28 +809,276 @@ where
         + 'static,
     Y: Default + Collider + Send + Sync + 'static,
 {
-    fn build(self, _world: &mut World, dispatcher: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
-        setup_dispatch_3d::<
-            f32,
-            P,
-            BodyPose<Point3<f32>, Quaternion<f32>>,
-            B,
-            TreeValueWrapped<Entity, B>,
-            Y,
-            _,
-            _,
-            DeltaTime<f32>,
-        >(
-            dispatcher,
-            SweepAndPrune3::<f32, B>::new(),
-            GJK3::new(),
-            self.spatial,
-        );
+    fn build(mut self, world: &mut World, dispatcher: &mut DispatcherBuilder<'a, 'b>) -> Result<(), Error> {
+        // Before registering, which would insert the default parameters
+        if let Some(ref config) = self.world {
+            world
+                .entry::<WorldParameters<Vector3<f32>, f32>>()
+                .or_insert_with(|| config.parameters());
+        }
+        register_3d::<P, B, Y>(world);
+        world
+            .entry::<PhysicsTimeScale>()
+            .or_insert_with(PhysicsTimeScale::default);
+        let names = self.names;
+        let dependencies = as_strs(&self.dependencies);
+        for route in self.routes.drain(..) {
+            route(world, &names, &mut self.systems);
+        }
+        if self.contacts {
+            self.systems.push(
+                PhysicsStage::PostContact,
+                SkipOnZeroDelta::new(ContactsSystem::<Point3<f32>>::new()),
+                &names.contacts,
+                &[],
+            );
+        }
+        if self.impacts {
+            self.systems.push(
+                PhysicsStage::PostContact,
+                SkipOnZeroDelta::new(ImpactSystem3::new()),
+                &names.impacts,
+                &[],
+            );
+        }
+        if self.collision_events {
+            self.systems.push(
+                PhysicsStage::PostContact,
+                SkipOnZeroDelta::new(CollisionTrackingSystem::<Point3<f32>>::new()),
+                &names.collision_tracking,
+                &[],
+            );
+            self.systems.push(
+                PhysicsStage::PostContact,
+                SensorEventSystem::<Point3<f32>>::new(),
+                &names.sensor_events,
+                &[&names.collision_tracking],
+            );
+        }
+        if let Some(ref path) = self.config_path {
+            dispatcher.add(
+                PhysicsConfigReloadSystem::<Vector3<f32>>::new(path),
+                &names.config_reload,
+                &dependencies,
+            );
+        }
+        // Added before the physics systems, so kinematic poses are in place when they run
+        dispatcher.add(TransformPoseSyncSystem3::new(), &names.transform_sync, &dependencies);
+        match self.fixed_step {
+            Some(fixed_step) => {
+                world.insert(fixed_step);
+                let mut physics = physics_dispatcher_builder(world);
+                setup_physics_3d::<P, B, Y>(
+                    &mut physics,
+                    self.spatial,
+                    self.broad_phase,
+                    &self.narrow_phase,
+                    &names,
+                    &[],
+                    &mut self.systems,
+                );
+                dispatcher.add_thread_local(FixedStepPhysicsSystem::new(physics.build()));
+                // Thread local, so the sync runs after the physics steps of this frame
+                dispatcher.add_thread_local(PoseTransformSyncSystem3::new().with_interpolation());
+                self.systems
+                    .add_stage_thread_local(PhysicsStage::PostSync, dispatcher);
+            }
+            None => {
+                setup_physics_3d::<P, B, Y>(
+                    dispatcher,
+                    self.spatial,
+                    self.broad_phase,
+                    &self.narrow_phase,
+                    &names,
+                    &dependencies,
+                    &mut self.systems,
+                );
+                dispatcher.add(
+                    PoseTransformSyncSystem3::new(),
+                    &names.sync,
+                    &[&names.solver],
+                );
+                self.systems
+                    .add_stage(PhysicsStage::PostSync, dispatcher, &[&names.sync]);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Register the components and insert the resources used by the 3D physics systems, so they
+/// exist even when no system fetches them before the entities are created.
+fn register_3d<P, B, Y>(world: &mut World)
+where
+    P: Primitive<Point = Point3<f32>> + Send + Sync + 'static,
+    B: Bound<Point = P::Point> + Send + Sync + 'static,
+    Y: Send + Sync + 'static,
+{
+    type T = BodyPose<Point3<f32>, Quaternion<f32>>;
+    world.register::<T>();
+    world.register::<NextFrame<T>>();
+    world.register::<Velocity3<f32>>();
+    world.register::<NextFrame<Velocity3<f32>>>();
+    world.register::<Mass3<f32>>();
+    world.register::<ForceAccumulator3<f32>>();
+    world.register::<PhysicalEntity<f32>>();
+    world.register::<CollisionShape<P, T, B, Y>>();
+    world.register::<ScaledShape<P, Y>>();
+    world.register::<Sensor>();
+    world.register::<Contacts<Point3<f32>>>();
+    world.register::<SyncDirection>();
+    world.register::<SyncMask>();
+    world.register::<VisualOffset>();
+    world
+        .entry::<WorldParameters<Vector3<f32>, f32>>()
+        .or_insert_with(WorldParameters::default);
+    world
+        .entry::<DeltaTime<f32>>()
+        .or_insert_with(DeltaTime::default);
+    world
+        .entry::<EventChannel<ContactEvent<Entity, Point3<f32>>>>()
+        .or_insert_with(EventChannel::default);
+    world
+        .entry::<EventChannel<CollisionEvent<Point3<f32>>>>()
+        .or_insert_with(EventChannel::default);
+    world
+        .entry::<EventChannel<ImpactEvent<Point3<f32>>>>()
+        .or_insert_with(EventChannel::default);
+    world
+        .entry::<EventChannel<SensorEvent>>()
+        .or_insert_with(EventChannel::default);
+}
+
+fn setup_physics_3d<P, B, Y>(
+    dispatcher: &mut DispatcherBuilder<'_, '_>,
+    spatial: bool,
+    broad_phase: BroadPhaseKind,
+    narrow_phase: &NarrowPhaseConfig,
+    names: &PhysicsSystemNames,
+    dependencies: &[&str],
+    systems: &mut StagedSystems,
+) where
+    P: Primitive<Point = Point3<f32>> + ComputeBound<B> + Send + Sync + 'static,
+    B: Bound<Point = P::Point>
+        + Clone
+        + Discrete<B>
+        + Union<B, Output = B>
+        + Contains<B>
+        + SurfaceArea<Scalar = f32>
+        + Debug
+        + Send
+        + Sync
+        + 'static,
+    Y: Default + Collider + Send + Sync + 'static,
+{
+    type T = BodyPose<Point3<f32>, Quaternion<f32>>;
+    dispatcher.add(
+        CurrentFrameUpdateSystem3::<f32, T>::new(),
+        &names.solver,
+        dependencies,
+    );
+    dispatcher.add(
+        SkipOnZeroDelta::new(NextFrameSetupSystem3::<f32, T>::new()),
+        &names.next_frame_setup,
+        &[&names.solver],
+    );
+    let after = systems.add_stage(
+        PhysicsStage::PreBroadPhase,
+        dispatcher,
+        &[&names.next_frame_setup],
+    );
+    let spatial = spatial || broad_phase == BroadPhaseKind::Dbvt;
+    let after = if spatial {
         dispatcher.add(
-            PoseTransformSyncSystem3::new(),
-            "sync_system",
-            &["physics_solver_system"],
+            SpatialSortingSystem::<P, T, TreeValueWrapped<Entity, B>, B, Y>::new(),
+            &names.spatial_sorting,
+            &as_strs(&after),
         );
-        Ok(())
+        vec![names.spatial_sorting.clone()]
+    } else {
+        after
+    };
+    let after = as_strs(&after);
+    match broad_phase {
+        BroadPhaseKind::SweepAndPrune => add_collision_system_3d::<P, B, Y, _>(
+            dispatcher,
+            spatial,
+            narrow_phase.gjk3(),
+            Some(SweepAndPrune3::<f32, B>::new()),
+            &names.collision,
+            &after,
+        ),
+        BroadPhaseKind::BruteForce => add_collision_system_3d::<P, B, Y, _>(
+            dispatcher,
+            spatial,
+            narrow_phase.gjk3(),
+            Some(BruteForce::default()),
+            &names.collision,
+            &after,
+        ),
+        // Spatial collision detection falls back to the tree without a broad phase
+        BroadPhaseKind::Dbvt => add_collision_system_3d::<P, B, Y, BruteForce>(
+            dispatcher,
+            spatial,
+            narrow_phase.gjk3(),
+            None,
+            &names.collision,
+            &after,
+        ),
+        BroadPhaseKind::SpatialHash { cell_size } => add_collision_system_3d::<P, B, Y, _>(
+            dispatcher,
+            spatial,
+            narrow_phase.gjk3(),
+            Some(SpatialHash::new(cell_size)),
+            &names.collision,
+            &after,
+        ),
+    }
+    let after = systems.add_stage(PhysicsStage::PostContact, dispatcher, &[&names.collision]);
+    dispatcher.add(
+        SkipOnZeroDelta::new(ContactResolutionSystem3::<f32, T>::new()),
+        &names.contact_resolution,
+        &as_strs(&after),
+    );
+    systems.add_stage(PhysicsStage::PostSolve, dispatcher, &[&names.contact_resolution]);
+}
+
+fn add_collision_system_3d<P, B, Y, V>(
+    dispatcher: &mut DispatcherBuilder<'_, '_>,
+    spatial: bool,
+    narrow_phase: GJK3<f32>,
+    broad_phase: Option<V>,
+    name: &str,
+    dependencies: &[&str],
+) where
+    P: Primitive<Point = Point3<f32>> + ComputeBound<B> + Send + Sync + 'static,
+    B: Bound<Point = P::Point>
+        + Clone
+        + Discrete<B>
+        + Union<B, Output = B>
+        + Contains<B>
+        + SurfaceArea<Scalar = f32>
+        + Debug
+        + Send
+        + Sync
+        + 'static,
+    Y: Default + Collider + Send + Sync + 'static,
+    V: BroadPhase<TreeValueWrapped<Entity, B>> + 'static,
+{
+    type T = BodyPose<Point3<f32>, Quaternion<f32>>;
+    if spatial {
+        let mut system = SpatialCollisionSystem::<P, T, TreeValueWrapped<Entity, B>, B, Y>::new()
+            .with_narrow_phase(narrow_phase);
+        if let Some(broad_phase) = broad_phase {
+            system = system.with_broad_phase(broad_phase);
+        }
+        dispatcher.add(SkipOnZeroDelta::new(system), name, dependencies);
+    } else {
+        let mut system = BasicCollisionSystem::<P, T, TreeValueWrapped<Entity, B>, B, Y>::new()
+            .with_narrow_phase(narrow_phase);
+        if let Some(broad_phase) = broad_phase {
+            system = system.with_broad_phase(broad_phase);
+        }
+        dispatcher.add(SkipOnZeroDelta::new(system), name, dependencies);
     }
 }
@@ -0,0 +1,332 @@
+use std::fs;
+use std::marker;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use amethyst_config::Config;
+use amethyst_core::ecs::{System, Write};
+use amethyst_error::Error;
+use cgmath::{Vector2, Vector3, VectorSpace};
+use log::{error, warn};
+use rhusics_core::WorldParameters;
+use rhusics_ecs::physics2d::GJK2;
+use rhusics_ecs::physics3d::GJK3;
+use serde::{Deserialize, Serialize};
+
+use crate::broad::BroadPhaseKind;
+use crate::step::FixedStepPhysics;
+
+/// Physics settings accepted by the physics bundles.
+///
+/// Can be loaded from a RON file with `PhysicsBundle2/3::from_config_path`, any settings missing
+/// from the file keep their defaults. Settings that are `None` keep those of the bundle. For
+/// example:
+///
+/// ```ron
+/// (
+///     world: Some((gravity: (0., -9.81, 0.), damping: 1.)),
+///     broad_phase: Some(SpatialHash(cell_size: 2.)),
+///     step: (fixed_step: Some(0.016666), max_substeps: 8),
+/// )
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PhysicsConfig {
+    /// Gravity and damping, or `None` to keep the `WorldParameters` in the world
+    pub world: Option<WorldConfig>,
+    /// Broad phase, or `None` to keep the one of the bundle
+    pub broad_phase: Option<BroadPhaseKind>,
+    /// Enable spatial sorting, or `None` to keep the setting of the bundle
+    pub spatial: Option<bool>,
+    /// Narrow phase settings
+    pub narrow_phase: NarrowPhaseConfig,
+    /// Time step settings
+    pub step: StepConfig,
+}
+
+impl PhysicsConfig {
+    /// Check the settings that would make the physics bundles panic
+    ///
+    /// The fixed step and the cell size of a `SpatialHash` must be positive and finite, and the
+    /// maximum number of substeps must be at least 1.
+    pub fn validate(&self) -> Result<(), Error> {
+        if let Some(step) = self.step.fixed_step {
+            if !(step.is_finite() && step > 0.) {
+                return Err(Error::from_string(format!(
+                    "physics fixed step must be positive and finite, got {}",
+                    step
+                )));
+            }
+        }
+        if self.step.max_substeps == 0 {
+            return Err(Error::from_string(
+                "physics max substeps must be at least 1",
+            ));
+        }
+        if let Some(BroadPhaseKind::SpatialHash { cell_size }) = self.broad_phase {
+            if !(cell_size.is_finite() && cell_size > 0.) {
+                return Err(Error::from_string(format!(
+                    "spatial hash cell size must be positive and finite, got {}",
+                    cell_size
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Gravity vectors that can be created from the gravity in `WorldConfig`.
+pub trait FromGravity {
+    /// Create from the gravity in `WorldConfig`, 2D vectors use the first two components
+    fn from_gravity(gravity: [f32; 3]) -> Self;
+}
+
+impl FromGravity for Vector2<f32> {
+    fn from_gravity(gravity: [f32; 3]) -> Self {
+        Vector2::new(gravity[0], gravity[1])
+    }
+}
+
+impl FromGravity for Vector3<f32> {
+    fn from_gravity(gravity: [f32; 3]) -> Self {
+        Vector3::new(gravity[0], gravity[1], gravity[2])
+    }
+}
+
+/// Settings for the `WorldParameters` resource.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldConfig {
+    /// Gravity, 2D physics uses the first two components
+    pub gravity: [f32; 3],
+    /// Velocity damping, `1.` is no damping
+    pub damping: f32,
+}
+
+impl WorldConfig {
+    /// Create `WorldParameters` with these settings
+    pub fn parameters<V>(&self) -> WorldParameters<V, f32>
+    where
+        V: FromGravity + VectorSpace<Scalar = f32>,
+    {
+        WorldParameters::new(V::from_gravity(self.gravity)).with_damping(self.damping)
+    }
+}
+
+impl Default for WorldConfig {
+    fn default() -> Self {
+        Self {
+            gravity: [0.; 3],
+            damping: 0.99,
+        }
+    }
+}
+
+/// Settings of the GJK narrow phase, and the EPA contact generation used with it.
+///
+/// The defaults are the same as those of `GJK2::new()` and `GJK3::new()`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NarrowPhaseConfig {
+    /// Tolerance of GJK distance computations
+    pub distance_tolerance: f32,
+    /// Tolerance of GJK time of impact computations, used by continuous collision detection
+    pub continuous_tolerance: f32,
+    /// Tolerance of EPA when computing contact normals and penetration depths
+    pub epa_tolerance: f32,
+    /// Maximum number of iterations of GJK and EPA
+    pub max_iterations: u32,
+}
+
+impl NarrowPhaseConfig {
+    /// Create a 2D narrow phase with these settings
+    pub fn gjk2(&self) -> GJK2<f32> {
+        GJK2::new_with_settings(
+            self.distance_tolerance,
+            self.continuous_tolerance,
+            self.epa_tolerance,
+            self.max_iterations,
+        )
+    }
+
+    /// Create a 3D narrow phase with these settings
+    pub fn gjk3(&self) -> GJK3<f32> {
+        GJK3::new_with_settings(
+            self.distance_tolerance,
+            self.continuous_tolerance,
+            self.epa_tolerance,
+            self.max_iterations,
+        )
+    }
+}
+
+impl Default for NarrowPhaseConfig {
+    fn default() -> Self {
+        Self {
+            distance_tolerance: 0.000_001,
+            continuous_tolerance: 0.000_001,
+            epa_tolerance: 0.000_01,
+            max_iterations: 100,
+        }
+    }
+}
+
+/// Time step settings of the physics simulation.
+///
+/// The contact resolution of rhusics has no settings of its own, so the step length is the only
+/// way to trade accuracy of the solver for speed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StepConfig {
+    /// Length of a fixed time step in seconds, or `None` to keep the setting of the bundle,
+    /// which steps once per frame unless `with_fixed_step` is used
+    pub fixed_step: Option<f32>,
+    /// Maximum number of fixed time steps to run in a single frame, at least 1
+    pub max_substeps: u32,
+}
+
+impl Default for StepConfig {
+    fn default() -> Self {
+        Self {
+            fixed_step: None,
+            max_substeps: 8,
+        }
+    }
+}
+
+/// System that reloads `PhysicsConfig` from a file when it changes.
+///
+/// Only the numeric parameters are reloaded, which are the `WorldParameters`, and the step length
+/// and maximum number of substeps of `FixedStepPhysics` when running with a fixed time step.
+/// Changing the narrow phase settings, the broad phase, spatial sorting or switching the fixed
+/// time step on or off needs a restart, a warning is logged when they change. Configs that fail
+/// `PhysicsConfig::validate` are logged and ignored. The file is checked at most once per second.
+///
+/// ### Type parameters:
+///
+/// - `V`: Gravity vector (`Vector2<f32>` or `Vector3<f32>`)
+pub struct PhysicsConfigReloadSystem<V> {
+    m: marker::PhantomData<V>,
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    checked: Option<Instant>,
+    config: Option<PhysicsConfig>,
+}
+
+impl<V> PhysicsConfigReloadSystem<V> {
+    /// Create new system, reloading the given file
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        Self {
+            m: marker::PhantomData,
+            modified: modified(&path),
+            config: PhysicsConfig::load(&path).ok(),
+            path,
+            checked: None,
+        }
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+/// Do the settings that can't be reloaded differ between the configs
+fn needs_restart(old: &PhysicsConfig, new: &PhysicsConfig) -> bool {
+    old.narrow_phase != new.narrow_phase
+        || old.broad_phase != new.broad_phase
+        || old.spatial != new.spatial
+        || old.step.fixed_step.is_some() != new.step.fixed_step.is_some()
+}
+
+impl<'a, V> System<'a> for PhysicsConfigReloadSystem<V>
+where
+    V: FromGravity + VectorSpace<Scalar = f32> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Write<'a, WorldParameters<V, f32>>,
+        Option<Write<'a, FixedStepPhysics>>,
+    );
+
+    fn run(&mut self, (mut parameters, fixed_step): Self::SystemData) {
+        if let Some(checked) = self.checked {
+            if checked.elapsed() < Duration::from_secs(1) {
+                return;
+            }
+        }
+        self.checked = Some(Instant::now());
+        let modified = modified(&self.path);
+        if modified == self.modified {
+            return;
+        }
+        self.modified = modified;
+        let config = PhysicsConfig::load(&self.path)
+            .map_err(Error::from)
+            .and_then(|config| config.validate().map(|_| config));
+        let config = match config {
+            Ok(config) => config,
+            Err(error) => {
+                error!(
+                    "Failed to reload physics config {}: {}",
+                    self.path.display(),
+                    error
+                );
+                return;
+            }
+        };
+        if let Some(ref world) = config.world {
+            *parameters = world.parameters();
+        }
+        if let (Some(mut fixed_step), Some(step)) = (fixed_step, config.step.fixed_step) {
+            fixed_step.set_step(step);
+            fixed_step.set_max_substeps(config.step.max_substeps);
+        }
+        if self
+            .config
+            .as_ref()
+            .map_or(false, |old| needs_restart(old, &config))
+        {
+            warn!(
+                "Physics config {} changed settings that only apply after a restart",
+                self.path.display()
+            );
+        }
+        self.config = Some(config);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::default::DefaultPhysicsBundle2;
+
+    use super::*;
+
+    fn load_invalid(name: &str, ron: &str) {
+        let path = env::temp_dir().join(format!("amethyst_rhusics_{}.ron", name));
+        fs::write(&path, ron).unwrap();
+        let bundle = DefaultPhysicsBundle2::<()>::from_config_path(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(bundle.is_err());
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        load_invalid("zero_step", "(step: (fixed_step: Some(0.)))");
+        load_invalid("negative_step", "(step: (fixed_step: Some(-0.1)))");
+        load_invalid(
+            "zero_substeps",
+            "(step: (fixed_step: Some(0.1), max_substeps: 0))",
+        );
+        load_invalid(
+            "zero_cell",
+            "(broad_phase: Some(SpatialHash(cell_size: 0.)))",
+        );
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(PhysicsConfig::default().validate().is_ok());
+    }
+}
@@ -0,0 +1,394 @@
+use std::collections::BTreeMap;
+
+use amethyst_core::ecs::{
+    Component, DenseVecStorage, Entities, Entity, Join, Read, System, SystemData, World, Write,
+    WriteStorage,
+};
+use cgmath::EuclideanSpace;
+use collision::Contact;
+use rhusics_core::ContactEvent;
+use shrev::{EventChannel, ReaderId};
+
+/// Collision event, telling new contacts apart from ongoing ones.
+///
+/// Sent by `CollisionTrackingSystem`, once per pair of colliding bodies and physics step.
+///
+/// ### Type parameters:
+///
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+#[derive(Debug, Clone)]
+pub enum CollisionEvent<P> {
+    /// The bodies started colliding in this step
+    Started {
+        /// The colliding bodies
+        bodies: (Entity, Entity),
+        /// Contact of this step
+        contact: Contact<P>,
+    },
+    /// The bodies were already colliding in the previous step
+    Persisting {
+        /// The colliding bodies
+        bodies: (Entity, Entity),
+        /// Contact of this step
+        contact: Contact<P>,
+    },
+    /// The bodies stopped colliding, or one of them was deleted
+    Ended {
+        /// The bodies that were colliding
+        bodies: (Entity, Entity),
+        /// Last contact between the bodies
+        contact: Contact<P>,
+    },
+}
+
+impl<P> CollisionEvent<P> {
+    /// The colliding bodies
+    pub fn bodies(&self) -> (Entity, Entity) {
+        match *self {
+            CollisionEvent::Started { bodies, .. }
+            | CollisionEvent::Persisting { bodies, .. }
+            | CollisionEvent::Ended { bodies, .. } => bodies,
+        }
+    }
+
+    /// Contact of this step, or the last contact for `Ended`
+    pub fn contact(&self) -> &Contact<P> {
+        match *self {
+            CollisionEvent::Started { ref contact, .. }
+            | CollisionEvent::Persisting { ref contact, .. }
+            | CollisionEvent::Ended { ref contact, .. } => contact,
+        }
+    }
+}
+
+/// System that tracks colliding pairs of bodies across physics steps, and sends
+/// `CollisionEvent`s for the `ContactEvent`s of the collision system.
+///
+/// Must run after the collision system in the same dispatcher, so it sees the contacts of every
+/// step, which `PhysicsBundle2/3::with_collision_events` takes care of. Pairs with a deleted
+/// body are ended, and contacts with deleted bodies are ignored. The bundles skip it while
+/// `DeltaTime` is zero, together with the collision system, so pausing doesn't end all pairs.
+///
+/// Events are sent in a deterministic order, all `Ended` events first and then the others, each
+/// ordered by the entities of the pair.
+///
+/// ### Type parameters:
+///
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+pub struct CollisionTrackingSystem<P> {
+    reader: Option<ReaderId<ContactEvent<Entity, P>>>,
+    pairs: BTreeMap<(Entity, Entity), ((Entity, Entity), Contact<P>)>,
+}
+
+impl<P> CollisionTrackingSystem<P> {
+    /// Create new system
+    pub fn new() -> Self {
+        Self {
+            reader: None,
+            pairs: BTreeMap::new(),
+        }
+    }
+}
+
+impl<P> Default for CollisionTrackingSystem<P> {
+    fn default() -> Self {
+        CollisionTrackingSystem::new()
+    }
+}
+
+/// Key of a pair of bodies, the same for both orders of the bodies
+pub(crate) fn pair_key((a, b): (Entity, Entity)) -> (Entity, Entity) {
+    if a <= b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+impl<'a, P> System<'a> for CollisionTrackingSystem<P>
+where
+    P: Clone + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, EventChannel<ContactEvent<Entity, P>>>,
+        Write<'a, EventChannel<CollisionEvent<P>>>,
+    );
+
+    fn run(&mut self, (entities, contacts, mut events): Self::SystemData) {
+        // Ordered by pair, so events are sent in the same order every run
+        let mut current = BTreeMap::new();
+        for event in contacts.read(self.reader.as_mut().unwrap()) {
+            let (a, b) = event.bodies;
+            if entities.is_alive(a) && entities.is_alive(b) {
+                current.insert(pair_key(event.bodies), (event.bodies, event.contact.clone()));
+            }
+        }
+
+        let mut collisions = Vec::new();
+        for (key, (bodies, contact)) in &self.pairs {
+            if !current.contains_key(key) {
+                collisions.push(CollisionEvent::Ended {
+                    bodies: *bodies,
+                    contact: contact.clone(),
+                });
+            }
+        }
+        for (key, (bodies, contact)) in &current {
+            let event = if self.pairs.contains_key(key) {
+                CollisionEvent::Persisting {
+                    bodies: *bodies,
+                    contact: contact.clone(),
+                }
+            } else {
+                CollisionEvent::Started {
+                    bodies: *bodies,
+                    contact: contact.clone(),
+                }
+            };
+            collisions.push(event);
+        }
+        self.pairs = current;
+        events.iter_write(collisions);
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<ContactEvent<Entity, P>>>()
+                .register_reader(),
+        );
+    }
+}
+
+/// Contact of an entity with another entity, see `Contacts`.
+///
+/// ### Type parameters:
+///
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactInfo<P>
+where
+    P: EuclideanSpace<Scalar = f32>,
+{
+    /// The other entity
+    pub other: Entity,
+    /// Contact normal, pointing from this entity towards the other
+    pub normal: P::Diff,
+    /// Penetration depth
+    pub penetration_depth: f32,
+    /// Contact point
+    pub contact_point: P,
+}
+
+/// Entities currently touching this entity.
+///
+/// Opt-in, only entities with the component get their contacts collected, which the physics
+/// bundles do after each physics step when `with_contacts` is used.
+///
+/// ### Type parameters:
+///
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+#[derive(Debug, Clone)]
+pub struct Contacts<P>
+where
+    P: EuclideanSpace<Scalar = f32>,
+{
+    contacts: Vec<ContactInfo<P>>,
+}
+
+impl<P> Contacts<P>
+where
+    P: EuclideanSpace<Scalar = f32>,
+{
+    /// Create new empty contacts
+    pub fn new() -> Self {
+        Self {
+            contacts: Vec::new(),
+        }
+    }
+
+    /// Contacts of the last physics step
+    pub fn contacts(&self) -> &[ContactInfo<P>] {
+        &self.contacts
+    }
+
+    /// Contact with the given entity, if touching it
+    pub fn with(&self, other: Entity) -> Option<&ContactInfo<P>> {
+        self.contacts.iter().find(|contact| contact.other == other)
+    }
+
+    /// No entities are touching
+    pub fn is_empty(&self) -> bool {
+        self.contacts.is_empty()
+    }
+}
+
+impl<P> Default for Contacts<P>
+where
+    P: EuclideanSpace<Scalar = f32>,
+{
+    fn default() -> Self {
+        Contacts::new()
+    }
+}
+
+impl<P> Component for Contacts<P>
+where
+    P: EuclideanSpace<Scalar = f32> + Send + Sync + 'static,
+    P::Diff: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// System that fills the `Contacts` components from the `ContactEvent`s of the collision system.
+///
+/// Must run after the collision system in the same dispatcher, which
+/// `PhysicsBundle2/3::with_contacts` takes care of.
+///
+/// ### Type parameters:
+///
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+pub struct ContactsSystem<P> {
+    reader: Option<ReaderId<ContactEvent<Entity, P>>>,
+}
+
+impl<P> ContactsSystem<P> {
+    /// Create new system
+    pub fn new() -> Self {
+        Self { reader: None }
+    }
+}
+
+impl<P> Default for ContactsSystem<P> {
+    fn default() -> Self {
+        ContactsSystem::new()
+    }
+}
+
+impl<'a, P> System<'a> for ContactsSystem<P>
+where
+    P: EuclideanSpace<Scalar = f32> + Send + Sync + 'static,
+    P::Diff: Send + Sync + 'static,
+{
+    type SystemData = (
+        Read<'a, EventChannel<ContactEvent<Entity, P>>>,
+        WriteStorage<'a, Contacts<P>>,
+    );
+
+    fn run(&mut self, (events, mut contacts): Self::SystemData) {
+        for contacts in (&mut contacts).join() {
+            contacts.contacts.clear();
+        }
+        for event in events.read(self.reader.as_mut().unwrap()) {
+            let (a, b) = event.bodies;
+            let contact = &event.contact;
+            if let Some(contacts) = contacts.get_mut(a) {
+                contacts.contacts.push(ContactInfo {
+                    other: b,
+                    normal: contact.normal,
+                    penetration_depth: contact.penetration_depth,
+                    contact_point: contact.contact_point,
+                });
+            }
+            if let Some(contacts) = contacts.get_mut(b) {
+                contacts.contacts.push(ContactInfo {
+                    other: a,
+                    normal: contact.normal * -1.,
+                    penetration_depth: contact.penetration_depth,
+                    contact_point: contact.contact_point,
+                });
+            }
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<ContactEvent<Entity, P>>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::ecs::{Builder, RunNow, WorldExt};
+    use cgmath::{Point2, Vector2};
+    use collision::CollisionStrategy;
+
+    use super::*;
+
+    type ContactEvents = EventChannel<ContactEvent<Entity, Point2<f32>>>;
+    type Collisions = EventChannel<CollisionEvent<Point2<f32>>>;
+
+    fn kinds(
+        world: &World,
+        reader: &mut ReaderId<CollisionEvent<Point2<f32>>>,
+    ) -> Vec<&'static str> {
+        world
+            .read_resource::<Collisions>()
+            .read(reader)
+            .map(|event| match *event {
+                CollisionEvent::Started { .. } => "started",
+                CollisionEvent::Persisting { .. } => "persisting",
+                CollisionEvent::Ended { .. } => "ended",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_collision_tracking() {
+        let mut world = World::new();
+        let mut system = CollisionTrackingSystem::<Point2<f32>>::new();
+        System::setup(&mut system, &mut world);
+        let mut reader = world.write_resource::<Collisions>().register_reader();
+        let a = world.create_entity().build();
+        let b = world.create_entity().build();
+        let contact = || ContactEvent::new((a, b), Contact::new(CollisionStrategy::CollisionOnly));
+
+        world.write_resource::<ContactEvents>().single_write(contact());
+        system.run_now(&world);
+        assert_eq!(vec!["started"], kinds(&world, &mut reader));
+
+        world.write_resource::<ContactEvents>().single_write(contact());
+        system.run_now(&world);
+        assert_eq!(vec!["persisting"], kinds(&world, &mut reader));
+
+        world.delete_entity(b).unwrap();
+        world.write_resource::<ContactEvents>().single_write(contact());
+        system.run_now(&world);
+        assert_eq!(vec!["ended"], kinds(&world, &mut reader));
+    }
+
+    #[test]
+    fn test_contacts() {
+        let mut world = World::new();
+        let mut system = ContactsSystem::<Point2<f32>>::new();
+        System::setup(&mut system, &mut world);
+        let a = world.create_entity().with(Contacts::<Point2<f32>>::new()).build();
+        let b = world.create_entity().with(Contacts::<Point2<f32>>::new()).build();
+        let mut contact = Contact::new(CollisionStrategy::FullResolution);
+        contact.normal = Vector2::new(1., 0.);
+        contact.penetration_depth = 0.5;
+        world
+            .write_resource::<ContactEvents>()
+            .single_write(ContactEvent::new((a, b), contact));
+        system.run_now(&world);
+        {
+            let contacts = world.read_storage::<Contacts<Point2<f32>>>();
+            let info_a = contacts.get(a).unwrap().with(b).unwrap();
+            let info_b = contacts.get(b).unwrap().with(a).unwrap();
+            assert_eq!(Vector2::new(1., 0.), info_a.normal);
+            assert_eq!(Vector2::new(-1., 0.), info_b.normal);
+            assert_eq!(0.5, info_b.penetration_depth);
+        }
+
+        system.run_now(&world);
+        let contacts = world.read_storage::<Contacts<Point2<f32>>>();
+        assert!(contacts.get(a).unwrap().is_empty());
+        assert!(contacts.get(b).unwrap().is_empty());
+    }
+}
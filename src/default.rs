@@ -1,9 +1,13 @@
-use cgmath::{Basis2, Point2, Point3, Quaternion};
+use cgmath::{Basis2, Matrix3, Point2, Point3, Quaternion, Vector3};
 use collision::primitive::{Primitive2, Primitive3};
 use collision::{Aabb2, Aabb3};
+use rhusics_core::BodyPose;
 
 use crate::bundle::{PhysicsBundle2, PhysicsBundle3};
-use crate::sync::PoseTransformSyncSystem;
+use crate::contacts::Contacts;
+use crate::impact::ImpactSystem;
+use crate::scale::ShapeScaleSystem;
+use crate::sync::{PoseTransformSyncSystem, TransformPoseSyncSystem};
 
 /// Utility type for a 2D sync system (from `BodyPose` to `Transform`).
 pub type PoseTransformSyncSystem2 = PoseTransformSyncSystem<Point2<f32>, Basis2<f32>>;
@@ -11,6 +15,40 @@ pub type PoseTransformSyncSystem2 = PoseTransformSyncSystem<Point2<f32>, Basis2<
 /// Utility type for a 3D sync system (from `BodyPose` to `Transform`).
 pub type PoseTransformSyncSystem3 = PoseTransformSyncSystem<Point3<f32>, Quaternion<f32>>;
 
+/// Utility type for a 2D reverse sync system (from `Transform` to `BodyPose`).
+pub type TransformPoseSyncSystem2 = TransformPoseSyncSystem<Point2<f32>, Basis2<f32>>;
+
+/// Utility type for a 3D reverse sync system (from `Transform` to `BodyPose`).
+pub type TransformPoseSyncSystem3 = TransformPoseSyncSystem<Point3<f32>, Quaternion<f32>>;
+
+/// Utility type for 2D contacts.
+pub type Contacts2 = Contacts<Point2<f32>>;
+
+/// Utility type for 3D contacts.
+pub type Contacts3 = Contacts<Point3<f32>>;
+
+/// Utility type for a 2D impact system.
+pub type ImpactSystem2 = ImpactSystem<Point2<f32>, Basis2<f32>, f32, f32>;
+
+/// Utility type for a 3D impact system.
+pub type ImpactSystem3 = ImpactSystem<Point3<f32>, Quaternion<f32>, Matrix3<f32>, Vector3<f32>>;
+
+/// Utility type for a 2D shape scale system.
+///
+/// ### Type parameters:
+///
+/// - `Y`: collision detection manager type (see `rhusics_core::Collider` for more information)
+pub type ShapeScaleSystem2<Y> =
+    ShapeScaleSystem<Primitive2<f32>, BodyPose<Point2<f32>, Basis2<f32>>, Aabb2<f32>, Y>;
+
+/// Utility type for a 3D shape scale system.
+///
+/// ### Type parameters:
+///
+/// - `Y`: collision detection manager type (see `rhusics_core::Collider` for more information)
+pub type ShapeScaleSystem3<Y> =
+    ShapeScaleSystem<Primitive3<f32>, BodyPose<Point3<f32>, Quaternion<f32>>, Aabb3<f32>, Y>;
+
 /// Utility type for a default 2D physics setup (including collision detection).
 ///
 /// ### Type parameters:
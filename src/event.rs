@@ -0,0 +1,136 @@
+use std::fmt::Debug;
+
+use amethyst::core::ecs::{Entity, Read, SystemData, World};
+use amethyst::core::shrev::{EventChannel, ReaderId};
+use amethyst::core::EventReader;
+use amethyst::input::{BindingTypes, InputEvent, StringBindings};
+use amethyst::ui::UiEvent;
+use amethyst::winit::Event;
+use amethyst::CoreApplication;
+use cgmath::{Point2, Point3};
+use rhusics_core::ContactEvent;
+
+/// State event including the contact events of the physics, for use instead of amethyst's
+/// `StateEvent`.
+///
+/// ### Type parameters:
+///
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+/// - `T`: Input bindings
+#[derive(Clone, Debug)]
+pub enum PhysicsStateEvent<P, T = StringBindings>
+where
+    T: BindingTypes,
+{
+    /// Events sent by the winit window
+    Window(Event),
+    /// Events sent by the ui system
+    Ui(UiEvent),
+    /// Events sent by the input system
+    Input(InputEvent<T>),
+    /// Contact events sent by the collision system
+    Collision(ContactEvent<Entity, P>),
+}
+
+/// 2D physics state event
+pub type PhysicsStateEvent2<T = StringBindings> = PhysicsStateEvent<Point2<f32>, T>;
+
+/// 3D physics state event
+pub type PhysicsStateEvent3<T = StringBindings> = PhysicsStateEvent<Point3<f32>, T>;
+
+/// Reader of `PhysicsStateEvent`s, used by the application to read events for the states.
+///
+/// ### Type parameters:
+///
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+/// - `T`: Input bindings
+pub struct PhysicsStateEventReader<P, T = StringBindings>
+where
+    T: BindingTypes,
+{
+    window: Option<ReaderId<Event>>,
+    ui: Option<ReaderId<UiEvent>>,
+    input: Option<ReaderId<InputEvent<T>>>,
+    collision: Option<ReaderId<ContactEvent<Entity, P>>>,
+}
+
+/// 2D physics state event reader
+pub type PhysicsStateEventReader2<T = StringBindings> = PhysicsStateEventReader<Point2<f32>, T>;
+
+/// 3D physics state event reader
+pub type PhysicsStateEventReader3<T = StringBindings> = PhysicsStateEventReader<Point3<f32>, T>;
+
+impl<P, T> Default for PhysicsStateEventReader<P, T>
+where
+    T: BindingTypes,
+{
+    fn default() -> Self {
+        Self {
+            window: None,
+            ui: None,
+            input: None,
+            collision: None,
+        }
+    }
+}
+
+impl<'a, P, T> EventReader<'a> for PhysicsStateEventReader<P, T>
+where
+    P: Clone + Debug + Send + Sync + 'static,
+    T: BindingTypes,
+{
+    type SystemData = (
+        Read<'a, EventChannel<Event>>,
+        Read<'a, EventChannel<UiEvent>>,
+        Read<'a, EventChannel<InputEvent<T>>>,
+        Read<'a, EventChannel<ContactEvent<Entity, P>>>,
+    );
+    type Event = PhysicsStateEvent<P, T>;
+
+    fn read(&mut self, data: Self::SystemData, events: &mut Vec<Self::Event>) {
+        let (window, ui, input, collision) = data;
+        events.extend(
+            window
+                .read(self.window.as_mut().expect("Reader not set up"))
+                .cloned()
+                .map(PhysicsStateEvent::Window),
+        );
+        events.extend(
+            ui.read(self.ui.as_mut().expect("Reader not set up"))
+                .cloned()
+                .map(PhysicsStateEvent::Ui),
+        );
+        events.extend(
+            input
+                .read(self.input.as_mut().expect("Reader not set up"))
+                .cloned()
+                .map(PhysicsStateEvent::Input),
+        );
+        events.extend(
+            collision
+                .read(self.collision.as_mut().expect("Reader not set up"))
+                .cloned()
+                .map(PhysicsStateEvent::Collision),
+        );
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        <Self::SystemData as SystemData<'a>>::setup(world);
+        self.window = Some(world.fetch_mut::<EventChannel<Event>>().register_reader());
+        self.ui = Some(world.fetch_mut::<EventChannel<UiEvent>>().register_reader());
+        self.input = Some(world.fetch_mut::<EventChannel<InputEvent<T>>>().register_reader());
+        self.collision = Some(
+            world
+                .fetch_mut::<EventChannel<ContactEvent<Entity, P>>>()
+                .register_reader(),
+        );
+    }
+}
+
+/// Application receiving 2D contact events in `State::handle_event`
+pub type PhysicsApplication2<'a, T, B = StringBindings> =
+    CoreApplication<'a, T, PhysicsStateEvent2<B>, PhysicsStateEventReader2<B>>;
+
+/// Application receiving 3D contact events in `State::handle_event`
+pub type PhysicsApplication3<'a, T, B = StringBindings> =
+    CoreApplication<'a, T, PhysicsStateEvent3<B>, PhysicsStateEventReader3<B>>;
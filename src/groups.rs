@@ -0,0 +1,128 @@
+use amethyst_core::ecs::{Component, DenseVecStorage};
+use rhusics_core::Collider;
+use serde::{Deserialize, Serialize};
+
+/// Collider type filtering collisions by layer, for use as `Y` in the physics bundles.
+///
+/// Each bit of the masks is a layer. Two shapes collide when each is a member of a layer the
+/// other one's filter accepts. The default is a member of all layers accepting all layers, so
+/// it collides with everything. See `CollisionMatrix` for named layers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CollisionGroups {
+    /// Layers the shape is a member of
+    pub membership: u32,
+    /// Layers the shape collides with
+    pub filter: u32,
+}
+
+impl CollisionGroups {
+    /// Mask with all layers set
+    pub const ALL: u32 = !0;
+
+    /// Mask with no layers set
+    pub const NONE: u32 = 0;
+
+    /// Create new collision groups
+    ///
+    /// ### Parameters:
+    ///
+    /// - `membership`: Layers the shape is a member of
+    /// - `filter`: Layers the shape collides with
+    pub fn new(membership: u32, filter: u32) -> Self {
+        Self { membership, filter }
+    }
+}
+
+impl Default for CollisionGroups {
+    fn default() -> Self {
+        CollisionGroups::new(CollisionGroups::ALL, CollisionGroups::ALL)
+    }
+}
+
+impl Collider for CollisionGroups {
+    fn should_generate_contacts(&self, other: &CollisionGroups) -> bool {
+        self.membership & other.filter != 0 && other.membership & self.filter != 0
+    }
+}
+
+impl Component for CollisionGroups {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Named collision layers and the pairs of them that collide.
+///
+/// Can be loaded from a RON file with `amethyst_config::Config::load`, for example:
+///
+/// ```ron
+/// (
+///     layers: ["wall", "player", "enemy", "pickup"],
+///     collisions: [
+///         ("player", "wall"),
+///         ("enemy", "wall"),
+///         ("player", "enemy"),
+///         ("player", "pickup"),
+///     ],
+/// )
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CollisionMatrix {
+    /// Names of the layers, at most 32
+    pub layers: Vec<String>,
+    /// Pairs of layers that collide, layers can be paired with themselves
+    pub collisions: Vec<(String, String)>,
+}
+
+impl CollisionMatrix {
+    /// Mask of the named layer, `None` for unknown layers and layers after the first 32
+    pub fn layer(&self, name: &str) -> Option<u32> {
+        self.layers
+            .iter()
+            .position(|layer| layer == name)
+            .filter(|&index| index < 32)
+            .map(|index| 1 << index)
+    }
+
+    /// Collision groups of a shape in the named layer, colliding with the layers paired with it
+    pub fn groups(&self, name: &str) -> Option<CollisionGroups> {
+        let membership = self.layer(name)?;
+        let filter = self
+            .collisions
+            .iter()
+            .filter_map(|(a, b)| {
+                if a == name {
+                    self.layer(b)
+                } else if b == name {
+                    self.layer(a)
+                } else {
+                    None
+                }
+            })
+            .fold(CollisionGroups::NONE, |filter, layer| filter | layer);
+        Some(CollisionGroups::new(membership, filter))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collision_matrix() {
+        let matrix = CollisionMatrix {
+            layers: vec!["wall".into(), "player".into(), "pickup".into()],
+            collisions: vec![
+                ("player".into(), "wall".into()),
+                ("pickup".into(), "player".into()),
+            ],
+        };
+        let wall = matrix.groups("wall").unwrap();
+        let player = matrix.groups("player").unwrap();
+        let pickup = matrix.groups("pickup").unwrap();
+        assert!(player.should_generate_contacts(&wall));
+        assert!(pickup.should_generate_contacts(&player));
+        assert!(!wall.should_generate_contacts(&wall));
+        assert!(!pickup.should_generate_contacts(&wall));
+        assert!(matrix.groups("enemy").is_none());
+    }
+}
@@ -0,0 +1,45 @@
+use amethyst_core::ecs::{Read, System, SystemData, World};
+use rhusics_ecs::DeltaTime;
+
+/// Wrapper that only runs the wrapped system when `DeltaTime` is not zero.
+///
+/// Rhusics consumes the contents of `ForceAccumulator`s when setting up the next frame, even
+/// when no time passes, so any forces added on the first frames or while paused would be lost.
+/// The physics bundles wrap the next frame setup and contact resolution systems in this, so
+/// forces and torques are kept until time moves again, and bodies stay put while it doesn't.
+///
+/// Systems reading the contact events are skipped together with the collision system, which
+/// the bundles also wrap, so contacts don't pile up unread in the event channel while time
+/// stands still, and aren't all resolved at once when it moves again.
+///
+/// ### Type parameters:
+///
+/// - `T`: Wrapped system
+pub struct SkipOnZeroDelta<T> {
+    system: T,
+}
+
+impl<T> SkipOnZeroDelta<T> {
+    /// Wrap a system
+    pub fn new(system: T) -> Self {
+        Self { system }
+    }
+}
+
+impl<'a, T> System<'a> for SkipOnZeroDelta<T>
+where
+    T: System<'a>,
+{
+    type SystemData = (Read<'a, DeltaTime<f32>>, T::SystemData);
+
+    fn run(&mut self, (delta, data): Self::SystemData) {
+        if delta.delta_seconds != 0. {
+            self.system.run(data);
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        <Read<'a, DeltaTime<f32>> as SystemData>::setup(world);
+        self.system.setup(world);
+    }
+}
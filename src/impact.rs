@@ -0,0 +1,303 @@
+use std::collections::BTreeMap;
+use std::marker;
+
+use amethyst_core::ecs::{Entity, Read, ReadStorage, System, SystemData, World, Write};
+use cgmath::num_traits::Zero;
+use cgmath::{EuclideanSpace, InnerSpace, Matrix3, Rotation, Vector2, Vector3};
+use rhusics_core::{
+    BodyPose, ContactEvent, Inertia, Mass, NextFrame, PhysicalEntity, Pose, Velocity,
+};
+use shrev::{EventChannel, ReaderId};
+
+use crate::contacts::pair_key;
+use crate::sensor::Sensor;
+
+/// Event reporting how hard two bodies hit each other, sent before resolving their contact.
+///
+/// Sent by `ImpactSystem`, once per pair of colliding bodies and physics step, for the deepest
+/// contact of the pair. Not sent for contacts with a `Sensor`, or with bodies without mass.
+///
+/// ### Type parameters:
+///
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImpactEvent<P>
+where
+    P: EuclideanSpace,
+{
+    /// The colliding bodies
+    pub bodies: (Entity, Entity),
+    /// Contact normal, pointing from the first body towards the second
+    pub normal: P::Diff,
+    /// Magnitude of the impulse along the normal that resolves the contact
+    pub normal_impulse: P::Scalar,
+    /// Velocity of the second body relative to the first at the contact point, before
+    /// resolving the contact
+    pub relative_velocity: P::Diff,
+    /// Kinetic energy the bodies lose when resolving the contact
+    pub energy_loss: P::Scalar,
+}
+
+/// Angular velocity types, with the rotational terms of an impact.
+///
+/// Implemented for `f32` in 2D, with a scalar inertia, and `Vector3<f32>` in 3D, with a
+/// `Matrix3<f32>` inertia tensor.
+///
+/// ### Type parameters:
+///
+/// - `V`: Vector from the center of mass to the contact point
+/// - `I`: Inverse inertia in world space
+pub trait ImpactAngular<V, I> {
+    /// Velocity of the point at `r` from the center of mass due to the rotation, `ω × r`
+    fn point_velocity(&self, r: &V) -> V;
+
+    /// Rotational term of the inverse effective mass along `normal`, `n · ((I⁻¹ (r × n)) × r)`
+    fn inverse_angular_mass(inverse_inertia: &I, r: &V, normal: &V) -> f32;
+}
+
+impl ImpactAngular<Vector2<f32>, f32> for f32 {
+    fn point_velocity(&self, r: &Vector2<f32>) -> Vector2<f32> {
+        Vector2::new(-self * r.y, self * r.x)
+    }
+
+    fn inverse_angular_mass(inverse_inertia: &f32, r: &Vector2<f32>, normal: &Vector2<f32>) -> f32 {
+        let r_cross_n = r.perp_dot(*normal);
+        inverse_inertia * r_cross_n * r_cross_n
+    }
+}
+
+impl ImpactAngular<Vector3<f32>, Matrix3<f32>> for Vector3<f32> {
+    fn point_velocity(&self, r: &Vector3<f32>) -> Vector3<f32> {
+        self.cross(*r)
+    }
+
+    fn inverse_angular_mass(
+        inverse_inertia: &Matrix3<f32>,
+        r: &Vector3<f32>,
+        normal: &Vector3<f32>,
+    ) -> f32 {
+        normal.dot((*inverse_inertia * r.cross(*normal)).cross(*r))
+    }
+}
+
+/// Impulse along the contact normal that resolves a collision.
+///
+/// ### Parameters:
+///
+/// - `closing_speed`: Speed of the bodies towards each other along the normal
+/// - `reduced_mass`: Effective mass of the bodies along the normal at the contact point, which
+///   is `m_a * m_b / (m_a + m_b)` when the bodies don't rotate
+/// - `restitution`: Coefficient of restitution, `1.` is perfectly elastic
+fn normal_impulse(closing_speed: f32, reduced_mass: f32, restitution: f32) -> f32 {
+    (1. + restitution) * reduced_mass * closing_speed
+}
+
+/// Kinetic energy lost when resolving a collision, see `normal_impulse` for the parameters.
+fn energy_loss(closing_speed: f32, reduced_mass: f32, restitution: f32) -> f32 {
+    0.5 * reduced_mass * (1. - restitution * restitution) * closing_speed * closing_speed
+}
+
+/// System that sends an `ImpactEvent` for each pair of colliding bodies.
+///
+/// Must run after the collision system and before contact resolution, in the same dispatcher,
+/// which the physics bundles take care of when `with_impacts` is used. The impulse and energy
+/// loss are those the contact resolution of rhusics applies for the velocities in `NextFrame`,
+/// using the lowest restitution of the two bodies, and taking the rotation of the bodies around
+/// the contact point into account. Bodies without a `NextFrame<BodyPose>` are treated as not
+/// rotating.
+///
+/// ### Type parameters:
+///
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+/// - `R`: Rotational quantity (`Basis2<f32>` or `Quaternion<f32>`)
+/// - `I`: Inertia, usually `f32` or `Matrix3<f32>`
+/// - `A`: Angular velocity, usually `f32` or `Vector3<f32>`
+pub struct ImpactSystem<P, R, I, A> {
+    reader: Option<ReaderId<ContactEvent<Entity, P>>>,
+    m: marker::PhantomData<(R, I, A)>,
+}
+
+impl<P, R, I, A> ImpactSystem<P, R, I, A> {
+    /// Create new system
+    pub fn new() -> Self {
+        Self {
+            reader: None,
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<P, R, I, A> Default for ImpactSystem<P, R, I, A> {
+    fn default() -> Self {
+        ImpactSystem::new()
+    }
+}
+
+/// Velocity of a body at the contact point, and the rotational term of its inverse effective
+/// mass along the normal.
+fn contact_point_terms<P, R, I, A>(
+    contact_point: P,
+    normal: &P::Diff,
+    mass: &Mass<f32, I>,
+    pose: Option<&NextFrame<BodyPose<P, R>>>,
+    velocity: Option<&NextFrame<Velocity<P::Diff, A>>>,
+) -> (P::Diff, f32)
+where
+    P: EuclideanSpace<Scalar = f32>,
+    P::Diff: InnerSpace,
+    R: Rotation<P>,
+    I: Inertia<Orientation = R>,
+    A: ImpactAngular<P::Diff, I> + Clone + Zero,
+{
+    let (linear, angular) = velocity
+        .map(|velocity| (*velocity.value.linear(), velocity.value.angular().clone()))
+        .unwrap_or_else(|| (P::Diff::zero(), A::zero()));
+    match pose {
+        Some(pose) => {
+            let r = contact_point - pose.value.position();
+            let inverse_inertia = mass.world_inverse_inertia(&pose.value.rotation());
+            (
+                linear + angular.point_velocity(&r),
+                A::inverse_angular_mass(&inverse_inertia, &r, normal),
+            )
+        }
+        None => (linear, 0.),
+    }
+}
+
+impl<'a, P, R, I, A> System<'a> for ImpactSystem<P, R, I, A>
+where
+    P: EuclideanSpace<Scalar = f32> + Send + Sync + 'static,
+    P::Diff: InnerSpace + Send + Sync + 'static,
+    R: Rotation<P> + Send + Sync + 'static,
+    I: Inertia<Orientation = R> + Send + Sync + 'static,
+    A: ImpactAngular<P::Diff, I> + Clone + Zero + Send + Sync + 'static,
+{
+    type SystemData = (
+        Read<'a, EventChannel<ContactEvent<Entity, P>>>,
+        ReadStorage<'a, Sensor>,
+        ReadStorage<'a, Mass<f32, I>>,
+        ReadStorage<'a, PhysicalEntity<f32>>,
+        ReadStorage<'a, NextFrame<BodyPose<P, R>>>,
+        ReadStorage<'a, NextFrame<Velocity<P::Diff, A>>>,
+        Write<'a, EventChannel<ImpactEvent<P>>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (contacts, sensors, masses, entities, poses, velocities, mut impacts) = data;
+        // Deepest contact of each pair, ordered by pair for a deterministic order of events
+        let mut pairs = BTreeMap::<(Entity, Entity), &ContactEvent<Entity, P>>::new();
+        for event in contacts.read(self.reader.as_mut().unwrap()) {
+            let (a, b) = event.bodies;
+            if sensors.contains(a) || sensors.contains(b) {
+                continue;
+            }
+            let deepest = pairs.entry(pair_key(event.bodies)).or_insert(event);
+            if event.contact.penetration_depth > deepest.contact.penetration_depth {
+                *deepest = event;
+            }
+        }
+        for event in pairs.values() {
+            let (a, b) = event.bodies;
+            let (mass_a, mass_b, entity_a, entity_b) =
+                match (masses.get(a), masses.get(b), entities.get(a), entities.get(b)) {
+                    (Some(mass_a), Some(mass_b), Some(entity_a), Some(entity_b)) => {
+                        (mass_a, mass_b, entity_a, entity_b)
+                    }
+                    _ => continue,
+                };
+            let contact = &event.contact;
+            let normal = contact.normal;
+            let (velocity_a, angular_a) = contact_point_terms(
+                contact.contact_point,
+                &normal,
+                mass_a,
+                poses.get(a),
+                velocities.get(a),
+            );
+            let (velocity_b, angular_b) = contact_point_terms(
+                contact.contact_point,
+                &normal,
+                mass_b,
+                poses.get(b),
+                velocities.get(b),
+            );
+            let inverse_mass =
+                mass_a.inverse_mass() + mass_b.inverse_mass() + angular_a + angular_b;
+            if inverse_mass == 0. {
+                continue;
+            }
+            let relative_velocity = velocity_b - velocity_a;
+            // Bodies that are already separating are left alone by the contact resolution
+            let closing_speed = (-relative_velocity.dot(normal)).max(0.);
+            let reduced_mass = 1. / inverse_mass;
+            let restitution = entity_a
+                .material()
+                .restitution()
+                .min(entity_b.material().restitution());
+            impacts.single_write(ImpactEvent {
+                bodies: event.bodies,
+                normal,
+                normal_impulse: normal_impulse(closing_speed, reduced_mass, restitution),
+                relative_velocity,
+                energy_loss: energy_loss(closing_speed, reduced_mass, restitution),
+            });
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<ContactEvent<Entity, P>>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::SquareMatrix;
+
+    use super::*;
+
+    #[test]
+    fn test_energy_loss() {
+        // Perfectly inelastic, all kinetic energy of the closing motion is lost
+        assert_eq!(4., energy_loss(2., 2., 0.));
+        assert_eq!(3., energy_loss(2., 2., 0.5));
+        assert_eq!(0., energy_loss(2., 2., 1.));
+    }
+
+    #[test]
+    fn test_resting_contact() {
+        assert_eq!(0., normal_impulse(0., 2., 0.5));
+        assert_eq!(0., energy_loss(0., 2., 0.5));
+    }
+
+    #[test]
+    fn test_elastic_contact() {
+        // Twice the momentum of the closing motion, and no energy lost
+        assert_eq!(12., normal_impulse(3., 2., 1.));
+        assert_eq!(0., energy_loss(3., 2., 1.));
+    }
+
+    #[test]
+    fn test_angular_2d() {
+        let r = Vector2::new(1., 3.);
+        assert_eq!(Vector2::new(-6., 2.), 2_f32.point_velocity(&r));
+        let normal = Vector2::new(0., 1.);
+        assert_eq!(0.5, f32::inverse_angular_mass(&0.5, &r, &normal));
+    }
+
+    #[test]
+    fn test_angular_3d() {
+        let angular = Vector3::new(0., 0., 2.);
+        let r = Vector3::new(1., 0., 0.);
+        assert_eq!(Vector3::new(0., 2., 0.), angular.point_velocity(&r));
+        let inverse_inertia = Matrix3::from_diagonal(Vector3::new(0.5, 0.5, 0.5));
+        let normal = Vector3::new(0., 1., 0.);
+        let angular_mass = Vector3::inverse_angular_mass(&inverse_inertia, &r, &normal);
+        assert_eq!(0.5, angular_mass);
+    }
+}
@@ -10,17 +10,61 @@ extern crate amethyst_core;
 // extern crate amethyst_renderer;
 extern crate cgmath;
 
-pub use self::arena::{setup_2d_arena, setup_3d_arena};
+pub use self::arena::{setup_2d_arena, setup_3d_arena, Arena2, Arena3};
+pub use self::broad::{BroadPhaseKind, SpatialHash};
 pub use self::bundle::{PhysicsBundle2, PhysicsBundle3};
+pub use self::config::{
+    FromGravity, NarrowPhaseConfig, PhysicsConfig, PhysicsConfigReloadSystem, StepConfig,
+    WorldConfig,
+};
+pub use self::contacts::{
+    CollisionEvent, CollisionTrackingSystem, ContactInfo, Contacts, ContactsSystem,
+};
 pub use self::default::{
-    DefaultPhysicsBundle2, DefaultPhysicsBundle3, PoseTransformSyncSystem2,
-    PoseTransformSyncSystem3,
+    Contacts2, Contacts3, DefaultPhysicsBundle2, DefaultPhysicsBundle3, ImpactSystem2,
+    ImpactSystem3, PoseTransformSyncSystem2, PoseTransformSyncSystem3, ShapeScaleSystem2,
+    ShapeScaleSystem3, TransformPoseSyncSystem2, TransformPoseSyncSystem3,
+};
+#[cfg(feature = "events")]
+pub use self::event::{
+    PhysicsApplication2, PhysicsApplication3, PhysicsStateEvent, PhysicsStateEvent2,
+    PhysicsStateEvent3, PhysicsStateEventReader, PhysicsStateEventReader2,
+    PhysicsStateEventReader3,
 };
 //pub use self::pick::{pick_ray, pick_ray_screen};
-pub use self::sync::{time_sync, AsTransform, Convert, PoseTransformSyncSystem};
+pub use self::groups::{CollisionGroups, CollisionMatrix};
+pub use self::guard::SkipOnZeroDelta;
+pub use self::impact::{ImpactAngular, ImpactEvent, ImpactSystem};
+pub use self::plane::Plane2Mapping;
+pub use self::route::{ContactRouteSystem, RoutedContact};
+pub use self::scale::{ScalePrimitive, ScaledShape, ShapeScaleSystem};
+pub use self::sensor::{Sensor, SensorEvent, SensorEventSystem};
+pub use self::stage::{PhysicsStage, PhysicsSystemNames};
+pub use self::step::{FixedStepPhysics, FixedStepPhysicsSystem, PhysicsTimeScale};
+pub use self::sync::{
+    time_sync, AsTransform, Convert, ConvertBack, Interpolate, InterpolationAlpha,
+    PoseTransformSyncSystem, SyncAxis, SyncDirection, SyncMask, TransformPoseSyncSystem,
+    VisualOffset,
+};
+pub use self::units::PhysicsUnits;
 
 mod arena;
+mod broad;
 mod bundle;
+mod config;
+mod contacts;
 mod default;
+#[cfg(feature = "events")]
+mod event;
+mod groups;
+mod guard;
+mod impact;
 //mod pick;
+mod plane;
+mod route;
+mod scale;
+mod sensor;
+mod stage;
+mod step;
 mod sync;
+mod units;
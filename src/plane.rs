@@ -0,0 +1,108 @@
+use amethyst_core::math as na;
+use cgmath::{Basis2, Point2, Rad, Rotation2};
+
+use crate::sync::{basis_angle, Convert, ConvertBack};
+
+/// Mapping of the 2D physics plane into amethysts 3D space.
+///
+/// 2D positions are placed on the plane, keeping the height above the plane that the `Transform`
+/// already had, and 2D rotations become rotations around the normal of the plane.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Plane2Mapping {
+    /// The XY plane, rotating around Z. This is the default.
+    XY,
+    /// The XZ plane, rotating around Y, for top down games with Y up.
+    ///
+    /// 2D `x` maps to X and 2D `y` maps to -Z, so the plane is seen from above.
+    XZ,
+    /// Arbitrary plane, 2D `x` and `y` map to the given axes, rotating around their cross
+    /// product. The axes must be orthonormal, use `Plane2Mapping::custom` to create this.
+    Custom {
+        /// World axis for 2D `x`
+        x: na::Vector3<f32>,
+        /// World axis for 2D `y`
+        y: na::Vector3<f32>,
+    },
+}
+
+impl Default for Plane2Mapping {
+    fn default() -> Self {
+        Plane2Mapping::XY
+    }
+}
+
+impl Plane2Mapping {
+    /// Create a custom plane mapping, from the world axes 2D `x` and `y` should map to.
+    ///
+    /// The axes are normalized, and `y` is made orthogonal to `x`.
+    pub fn custom(x: na::Vector3<f32>, y: na::Vector3<f32>) -> Self {
+        let x = x.normalize();
+        let y = (y - x * x.dot(&y)).normalize();
+        Plane2Mapping::Custom { x, y }
+    }
+
+    /// World axes of 2D `x`, 2D `y` and the plane normal
+    pub fn axes(&self) -> (na::Vector3<f32>, na::Vector3<f32>, na::Vector3<f32>) {
+        match *self {
+            Plane2Mapping::XY => (na::Vector3::x(), na::Vector3::y(), na::Vector3::z()),
+            Plane2Mapping::XZ => (na::Vector3::x(), -na::Vector3::z(), na::Vector3::y()),
+            Plane2Mapping::Custom { x, y } => (x, y, x.cross(&y)),
+        }
+    }
+
+    /// Map a 2D position onto the plane, keeping the height of `original` above the plane.
+    pub fn to_world(&self, point: &Point2<f32>, original: &na::Vector3<f32>) -> na::Vector3<f32> {
+        let (x, y, normal) = self.axes();
+        x * point.x + y * point.y + normal * original.dot(&normal)
+    }
+
+    /// Project a world position onto the plane.
+    pub fn to_plane(&self, world: &na::Vector3<f32>) -> Point2<f32> {
+        let (x, y, _) = self.axes();
+        Point2::new(world.dot(&x), world.dot(&y))
+    }
+
+    /// Map a 2D rotation to a rotation around the plane normal.
+    pub fn rotation_to_world(&self, rotation: &Basis2<f32>) -> na::UnitQuaternion<f32> {
+        match *self {
+            Plane2Mapping::XY => rotation.convert(na::UnitQuaternion::identity()),
+            _ => {
+                let (_, _, normal) = self.axes();
+                // Same direction of rotation as the XY conversion of `Basis2`
+                na::UnitQuaternion::from_axis_angle(
+                    &na::Unit::new_normalize(normal),
+                    -basis_angle(rotation),
+                )
+            }
+        }
+    }
+
+    /// Get the rotation around the plane normal of a world rotation, as a 2D rotation.
+    pub fn rotation_to_plane(&self, rotation: &na::UnitQuaternion<f32>) -> Basis2<f32> {
+        match *self {
+            Plane2Mapping::XY => Basis2::convert_back(rotation),
+            _ => {
+                let (x, y, _) = self.axes();
+                let rotated = rotation * x;
+                Basis2::from_angle(Rad(-rotated.dot(&y).atan2(rotated.dot(&x))))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xz_round_trip() {
+        let plane = Plane2Mapping::XZ;
+        let world = plane.to_world(&Point2::new(1., 2.), &na::Vector3::new(5., 3., 7.));
+        assert_eq!(na::Vector3::new(1., 3., -2.), world);
+        assert_eq!(Point2::new(1., 2.), plane.to_plane(&world));
+
+        let rotation = Basis2::from_angle(Rad(0.5));
+        let back = plane.rotation_to_plane(&plane.rotation_to_world(&rotation));
+        assert!((basis_angle(&back) - 0.5).abs() < 1e-5);
+    }
+}
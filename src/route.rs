@@ -0,0 +1,204 @@
+use std::fmt;
+use std::marker;
+
+use amethyst_core::ecs::{Component, Entity, Read, ReadStorage, System, SystemData, World, Write};
+use cgmath::EuclideanSpace;
+use collision::Contact;
+use rhusics_core::ContactEvent;
+use shrev::{EventChannel, ReaderId};
+
+/// Contact between bodies matching the filters of a contact route, see
+/// `PhysicsBundle2/3::route_contacts`.
+///
+/// ### Type parameters:
+///
+/// - `R`: Tag type of the route, telling the channels of different routes apart
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+pub struct RoutedContact<R, P>
+where
+    P: EuclideanSpace,
+{
+    /// The bodies, the first one matching the first filter of the route
+    pub bodies: (Entity, Entity),
+    /// The contact, with the normal pointing from the first body towards the second
+    pub contact: Contact<P>,
+    m: marker::PhantomData<fn() -> R>,
+}
+
+impl<R, P> Clone for RoutedContact<R, P>
+where
+    P: EuclideanSpace,
+{
+    fn clone(&self) -> Self {
+        Self {
+            bodies: self.bodies,
+            contact: self.contact.clone(),
+            m: marker::PhantomData,
+        }
+    }
+}
+
+impl<R, P> fmt::Debug for RoutedContact<R, P>
+where
+    P: EuclideanSpace + fmt::Debug,
+    P::Diff: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RoutedContact")
+            .field("bodies", &self.bodies)
+            .field("contact", &self.contact)
+            .finish()
+    }
+}
+
+type Filter<Y> = Box<dyn Fn(&Y) -> bool + Send + Sync>;
+
+/// System that sends the contacts between bodies matching a pair of filters to their own
+/// channel, as `RoutedContact`s.
+///
+/// The filters are tested against the `Y` component of the bodies, a copy of the collider type
+/// in their `CollisionShape`, which can't be read from the shape. Contacts with bodies without
+/// it are skipped. The bodies are swapped when needed, so the first body always matches the
+/// first filter.
+///
+/// ### Type parameters:
+///
+/// - `R`: Tag type of the route, telling the channels of different routes apart
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+/// - `Y`: collision detection manager type (see `rhusics_core::Collider` for more information)
+pub struct ContactRouteSystem<R, P, Y> {
+    first: Filter<Y>,
+    second: Filter<Y>,
+    reader: Option<ReaderId<ContactEvent<Entity, P>>>,
+    m: marker::PhantomData<fn() -> R>,
+}
+
+impl<R, P, Y> ContactRouteSystem<R, P, Y> {
+    /// Create new system
+    ///
+    /// ### Parameters:
+    ///
+    /// - `first`: Filter of the first body
+    /// - `second`: Filter of the second body
+    pub fn new<F, G>(first: F, second: G) -> Self
+    where
+        F: Fn(&Y) -> bool + Send + Sync + 'static,
+        G: Fn(&Y) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            first: Box::new(first),
+            second: Box::new(second),
+            reader: None,
+            m: marker::PhantomData,
+        }
+    }
+
+    /// Whether the bodies match the filters, and if they need to be swapped for it
+    fn route(&self, a: &Y, b: &Y) -> Option<bool> {
+        if (self.first)(a) && (self.second)(b) {
+            Some(false)
+        } else if (self.first)(b) && (self.second)(a) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a, R, P, Y> System<'a> for ContactRouteSystem<R, P, Y>
+where
+    R: 'static,
+    P: EuclideanSpace<Scalar = f32> + Send + Sync + 'static,
+    P::Diff: Send + Sync + 'static,
+    Y: Component,
+{
+    type SystemData = (
+        ReadStorage<'a, Y>,
+        Read<'a, EventChannel<ContactEvent<Entity, P>>>,
+        Write<'a, EventChannel<RoutedContact<R, P>>>,
+    );
+
+    fn run(&mut self, (colliders, contacts, mut routed): Self::SystemData) {
+        for event in contacts.read(self.reader.as_mut().unwrap()) {
+            let (a, b) = event.bodies;
+            let swapped = match (colliders.get(a), colliders.get(b)) {
+                (Some(y_a), Some(y_b)) => self.route(y_a, y_b),
+                _ => None,
+            };
+            let contact = match swapped {
+                Some(false) => RoutedContact {
+                    bodies: (a, b),
+                    contact: event.contact.clone(),
+                    m: marker::PhantomData,
+                },
+                Some(true) => {
+                    let mut contact = event.contact.clone();
+                    contact.normal = contact.normal * -1.;
+                    RoutedContact {
+                        bodies: (b, a),
+                        contact,
+                        m: marker::PhantomData,
+                    }
+                }
+                None => continue,
+            };
+            routed.single_write(contact);
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<ContactEvent<Entity, P>>>()
+                .register_reader(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::ecs::{Builder, DenseVecStorage, RunNow, WorldExt};
+    use cgmath::{Point2, Vector2};
+    use collision::CollisionStrategy;
+
+    use super::*;
+
+    struct Kind(u8);
+
+    impl Component for Kind {
+        type Storage = DenseVecStorage<Self>;
+    }
+
+    struct Route;
+
+    #[test]
+    fn test_route_swapped_pair() {
+        let mut world = World::new();
+        let mut system = ContactRouteSystem::<Route, Point2<f32>, Kind>::new(
+            |kind| kind.0 == 1,
+            |kind| kind.0 == 2,
+        );
+        System::setup(&mut system, &mut world);
+        let mut reader = world
+            .write_resource::<EventChannel<RoutedContact<Route, Point2<f32>>>>()
+            .register_reader();
+        let a = world.create_entity().with(Kind(1)).build();
+        let b = world.create_entity().with(Kind(2)).build();
+        let mut contact = Contact::new(CollisionStrategy::FullResolution);
+        contact.normal = Vector2::new(1., 0.);
+        world
+            .write_resource::<EventChannel<ContactEvent<Entity, Point2<f32>>>>()
+            .single_write(ContactEvent::new((b, a), contact));
+        system.run_now(&world);
+
+        let routed = world
+            .read_resource::<EventChannel<RoutedContact<Route, Point2<f32>>>>()
+            .read(&mut reader)
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(1, routed.len());
+        assert_eq!((a, b), routed[0].bodies);
+        assert_eq!(Vector2::new(-1., 0.), routed[0].contact.normal);
+    }
+}
@@ -0,0 +1,256 @@
+use std::marker;
+
+use amethyst_core::ecs::{Component, DenseVecStorage, Join, ReadStorage, System, WriteStorage};
+use amethyst_core::math as na;
+use amethyst_core::Transform;
+use cgmath::{Point2, Transform as CgTransform};
+use collision::primitive::{
+    Circle, ConvexPolygon, Cuboid, Primitive2, Primitive3, Rectangle, Sphere,
+};
+use collision::{Bound, ComputeBound, Primitive, Union};
+use rhusics_core::{CollisionMode, CollisionShape, CollisionStrategy};
+
+use crate::plane::Plane2Mapping;
+
+/// Utility trait for scaling collision primitives along the axes of a `Transform`.
+///
+/// 2D primitives are scaled by the X and Y scale, or the scale along the axes of a
+/// `Plane2Mapping` with `scaled_on`. Circles and spheres stay round, and are scaled by the
+/// largest of their scale factors.
+///
+/// Supported are rectangles, circles and convex polygons in 2D, and cuboids and spheres in 3D.
+/// Other primitives, including `Square`, `Quad` and `Cube`, are left as they are; use a
+/// `Rectangle` or `Cuboid` for boxes that should scale.
+pub trait ScalePrimitive: Sized {
+    /// Scale the primitive
+    fn scaled(&self, scale: &na::Vector3<f32>) -> Self;
+
+    /// Scale the primitive, with 2D primitives lying on the given plane
+    ///
+    /// 3D primitives ignore the plane, and are scaled as by `scaled`.
+    fn scaled_on(&self, scale: &na::Vector3<f32>, _plane: &Plane2Mapping) -> Self {
+        self.scaled(scale)
+    }
+}
+
+impl ScalePrimitive for Primitive2<f32> {
+    fn scaled(&self, scale: &na::Vector3<f32>) -> Self {
+        let scale = scale.abs();
+        match *self {
+            Primitive2::Rectangle(ref rectangle) => Rectangle::new(
+                rectangle.dim().x * scale.x,
+                rectangle.dim().y * scale.y,
+            )
+            .into(),
+            Primitive2::Circle(ref circle) => {
+                Circle::new(circle.radius * scale.x.max(scale.y)).into()
+            }
+            Primitive2::ConvexPolygon(ref polygon) => ConvexPolygon::new(
+                polygon
+                    .vertices
+                    .iter()
+                    .map(|v| Point2::new(v.x * scale.x, v.y * scale.y))
+                    .collect(),
+            )
+            .into(),
+            ref primitive => primitive.clone(),
+        }
+    }
+
+    fn scaled_on(&self, scale: &na::Vector3<f32>, plane: &Plane2Mapping) -> Self {
+        // Scale along the world axes the 2D axes map to
+        let (x, y, normal) = plane.axes();
+        self.scaled(&na::Vector3::new(
+            scale.component_mul(&x).norm(),
+            scale.component_mul(&y).norm(),
+            scale.component_mul(&normal).norm(),
+        ))
+    }
+}
+
+impl ScalePrimitive for Primitive3<f32> {
+    fn scaled(&self, scale: &na::Vector3<f32>) -> Self {
+        let scale = scale.abs();
+        match *self {
+            Primitive3::Cuboid(ref cuboid) => Cuboid::new(
+                cuboid.dim().x * scale.x,
+                cuboid.dim().y * scale.y,
+                cuboid.dim().z * scale.z,
+            )
+            .into(),
+            Primitive3::Sphere(ref sphere) => {
+                Sphere::new(sphere.radius * scale.x.max(scale.y).max(scale.z)).into()
+            }
+            ref primitive => primitive.clone(),
+        }
+    }
+}
+
+/// Unscaled collision shape of an entity, used by `ShapeScaleSystem` to rebuild the
+/// `CollisionShape` when the scale of the `Transform` changes.
+///
+/// ### Type parameters:
+///
+/// - `P`: Collision primitive (see `collision::primitive` for more information)
+/// - `Y`: collision detection manager type (see `rhusics_core::Collider` for more information)
+#[derive(Debug, Clone)]
+pub struct ScaledShape<P, Y> {
+    strategy: CollisionStrategy,
+    mode: CollisionMode,
+    primitive: P,
+    ty: Y,
+    applied: Option<na::Vector3<f32>>,
+}
+
+impl<P, Y> ScaledShape<P, Y> {
+    /// Create new scaled shape, with the default collider type
+    pub fn new(strategy: CollisionStrategy, mode: CollisionMode, primitive: P) -> Self
+    where
+        Y: Default,
+    {
+        Self::new_with_type(strategy, mode, primitive, Y::default())
+    }
+
+    /// Create new scaled shape
+    ///
+    /// ### Parameters:
+    ///
+    /// - `strategy`: Collision strategy of the shape
+    /// - `mode`: Collision mode of the shape
+    /// - `primitive`: Primitive at a scale of one
+    /// - `ty`: Collider type
+    pub fn new_with_type(
+        strategy: CollisionStrategy,
+        mode: CollisionMode,
+        primitive: P,
+        ty: Y,
+    ) -> Self {
+        Self {
+            strategy,
+            mode,
+            primitive,
+            ty,
+            applied: None,
+        }
+    }
+
+    /// Primitive at a scale of one
+    pub fn primitive(&self) -> &P {
+        &self.primitive
+    }
+}
+
+impl<P, Y> Component for ScaledShape<P, Y>
+where
+    P: Send + Sync + 'static,
+    Y: Send + Sync + 'static,
+{
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// System that scales `CollisionShape` primitives by the scale of the `Transform`.
+///
+/// Only entities with a `ScaledShape` are scaled. The `CollisionShape` is rebuilt from it when
+/// the scale changes, which recomputes the bounds, so the broad phase stays correct. Should be
+/// added before the physics systems. See `ScalePrimitive` for the primitives that are scaled.
+///
+/// ### Type parameters:
+///
+/// - `P`: Collision primitive (see `collision::primitive` for more information)
+/// - `T`: Transform type (`BodyPose2` or similar)
+/// - `B`: Bounding volume (`Aabb2`, `Aabb3` or `Sphere` for most scenarios)
+/// - `Y`: collision detection manager type (see `rhusics_core::Collider` for more information)
+pub struct ShapeScaleSystem<P, T, B, Y> {
+    m: marker::PhantomData<(P, T, B, Y)>,
+    plane: Plane2Mapping,
+}
+
+impl<P, T, B, Y> ShapeScaleSystem<P, T, B, Y> {
+    /// Create new system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+            plane: Plane2Mapping::default(),
+        }
+    }
+
+    /// Scale 2D primitives along the axes of the given plane, instead of X and Y
+    ///
+    /// Should match the plane of the sync systems. Has no effect on 3D primitives.
+    pub fn with_plane(mut self, plane: Plane2Mapping) -> Self {
+        self.plane = plane;
+        self
+    }
+}
+
+impl<P, T, B, Y> Default for ShapeScaleSystem<P, T, B, Y> {
+    fn default() -> Self {
+        ShapeScaleSystem::new()
+    }
+}
+
+impl<'a, P, T, B, Y> System<'a> for ShapeScaleSystem<P, T, B, Y>
+where
+    P: Primitive + ComputeBound<B> + ScalePrimitive + Send + Sync + 'static,
+    T: CgTransform<P::Point> + Component + Send + Sync + 'static,
+    B: Bound<Point = P::Point> + Union<B, Output = B> + Clone + Send + Sync + 'static,
+    Y: Clone + Send + Sync + 'static,
+{
+    type SystemData = (
+        ReadStorage<'a, Transform>,
+        ReadStorage<'a, T>,
+        WriteStorage<'a, ScaledShape<P, Y>>,
+        WriteStorage<'a, CollisionShape<P, T, B, Y>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (transforms, poses, mut scaled_shapes, mut shapes) = data;
+        for (transform, pose, scaled_shape, shape) in
+            (&transforms, &poses, &mut scaled_shapes, &mut shapes).join()
+        {
+            let scale = *transform.scale();
+            if scaled_shape.applied == Some(scale) {
+                continue;
+            }
+            let enabled = shape.enabled;
+            *shape = CollisionShape::new_simple_with_type(
+                scaled_shape.strategy.clone(),
+                scaled_shape.mode.clone(),
+                scaled_shape.primitive.scaled_on(&scale, &self.plane),
+                scaled_shape.ty.clone(),
+            );
+            shape.enabled = enabled;
+            shape.update(pose, None);
+            scaled_shape.applied = Some(scale);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_rectangle() {
+        let rectangle: Primitive2<f32> = Rectangle::new(2., 4.).into();
+        match rectangle.scaled(&na::Vector3::new(1.5, -0.5, 1.)) {
+            Primitive2::Rectangle(scaled) => {
+                assert_eq!(3., scaled.dim().x);
+                assert_eq!(2., scaled.dim().y);
+            }
+            _ => panic!("Expected a rectangle"),
+        }
+    }
+
+    #[test]
+    fn test_scale_rectangle_on_xz() {
+        let rectangle: Primitive2<f32> = Rectangle::new(2., 4.).into();
+        match rectangle.scaled_on(&na::Vector3::new(1.5, 3., 0.5), &Plane2Mapping::XZ) {
+            Primitive2::Rectangle(scaled) => {
+                assert_eq!(3., scaled.dim().x);
+                assert_eq!(2., scaled.dim().y);
+            }
+            _ => panic!("Expected a rectangle"),
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use amethyst_core::ecs::{
+    Component, Entity, NullStorage, Read, ReadStorage, System, SystemData, World, Write,
+};
+use cgmath::Transform;
+use collision::{Bound, ComputeBound, Primitive, Union};
+use rhusics_core::{CollisionMode, CollisionShape, CollisionStrategy};
+use shrev::{EventChannel, ReaderId};
+
+use crate::contacts::CollisionEvent;
+
+/// Marker for trigger volumes, such as pickups, checkpoints or damage zones.
+///
+/// Sensors need a `CollisionShape` with `CollisionStrategy::CollisionOnly`, best created with
+/// `Sensor::shape`. Their contacts are then sent as usual, but without a normal or penetration
+/// depth, so contact resolution leaves them alone, and sensors don't push bodies and aren't
+/// pushed by them.
+///
+/// `SensorEvent`s for overlaps starting and ending are only sent when collision events are
+/// enabled, with `PhysicsBundle2/3::with_collision_events`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sensor;
+
+impl Component for Sensor {
+    type Storage = NullStorage<Self>;
+}
+
+impl Sensor {
+    /// Create a `CollisionOnly` shape for a sensor, with the default collider type
+    pub fn shape<P, T, B, Y>(primitive: P) -> CollisionShape<P, T, B, Y>
+    where
+        P: Primitive + ComputeBound<B>,
+        B: Bound<Point = P::Point> + Union<B, Output = B> + Clone,
+        T: Transform<P::Point>,
+        Y: Default,
+    {
+        Sensor::shape_with_type(primitive, Y::default())
+    }
+
+    /// Create a `CollisionOnly` shape for a sensor
+    ///
+    /// ### Parameters:
+    ///
+    /// - `primitive`: Primitive of the shape
+    /// - `ty`: Collider type
+    pub fn shape_with_type<P, T, B, Y>(primitive: P, ty: Y) -> CollisionShape<P, T, B, Y>
+    where
+        P: Primitive + ComputeBound<B>,
+        B: Bound<Point = P::Point> + Union<B, Output = B> + Clone,
+        T: Transform<P::Point>,
+    {
+        CollisionShape::new_simple_with_type(
+            CollisionStrategy::CollisionOnly,
+            CollisionMode::Discrete,
+            primitive,
+            ty,
+        )
+    }
+}
+
+/// Event sent when a body starts or stops overlapping a sensor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensorEvent {
+    /// The body started overlapping the sensor
+    Entered {
+        /// The sensor
+        sensor: Entity,
+        /// The body, which may be another sensor
+        body: Entity,
+    },
+    /// The body stopped overlapping the sensor, or one of them was deleted
+    Exited {
+        /// The sensor
+        sensor: Entity,
+        /// The body, which may be another sensor
+        body: Entity,
+    },
+}
+
+/// System that sends `SensorEvent`s for the `CollisionEvent`s involving a `Sensor`.
+///
+/// When both bodies are sensors, an event is sent for each of them.
+///
+/// ### Type parameters:
+///
+/// - `P`: Contact point (`Point2<f32>` or `Point3<f32>`)
+pub struct SensorEventSystem<P> {
+    reader: Option<ReaderId<CollisionEvent<P>>>,
+}
+
+impl<P> SensorEventSystem<P> {
+    /// Create new system
+    pub fn new() -> Self {
+        Self { reader: None }
+    }
+}
+
+impl<P> Default for SensorEventSystem<P> {
+    fn default() -> Self {
+        SensorEventSystem::new()
+    }
+}
+
+impl<'a, P> System<'a> for SensorEventSystem<P>
+where
+    P: Send + Sync + 'static,
+{
+    type SystemData = (
+        ReadStorage<'a, Sensor>,
+        Read<'a, EventChannel<CollisionEvent<P>>>,
+        Write<'a, EventChannel<SensorEvent>>,
+    );
+
+    fn run(&mut self, (sensors, collisions, mut events): Self::SystemData) {
+        for collision in collisions.read(self.reader.as_mut().unwrap()) {
+            let (a, b) = collision.bodies();
+            for &(sensor, body) in &[(a, b), (b, a)] {
+                if !sensors.contains(sensor) {
+                    continue;
+                }
+                match *collision {
+                    CollisionEvent::Started { .. } => {
+                        events.single_write(SensorEvent::Entered { sensor, body })
+                    }
+                    CollisionEvent::Ended { .. } => {
+                        events.single_write(SensorEvent::Exited { sensor, body })
+                    }
+                    CollisionEvent::Persisting { .. } => (),
+                }
+            }
+        }
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        Self::SystemData::setup(world);
+        self.reader = Some(
+            world
+                .fetch_mut::<EventChannel<CollisionEvent<P>>>()
+                .register_reader(),
+        );
+    }
+}
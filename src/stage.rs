@@ -0,0 +1,161 @@
+use amethyst_core::ecs::{DispatcherBuilder, System};
+
+/// Stages of the physics systems, where the physics bundles can add user systems.
+///
+/// When running with a fixed time step, all stages but `PostSync` run once per physics step, and
+/// the `PostSync` systems run as thread local systems after the physics steps of the frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhysicsStage {
+    /// After next frame setup, before spatial sorting and the broad phase
+    PreBroadPhase,
+    /// After collision detection, before contact resolution
+    PostContact,
+    /// After contact resolution
+    PostSolve,
+    /// After the sync system has written the `Transform`s
+    PostSync,
+}
+
+/// Names of the systems added by the physics bundles.
+///
+/// Only names of systems in the main dispatcher can be used as dependencies of other systems.
+/// When running with a fixed time step, those are just `config_reload` and `transform_sync`:
+/// the other physics systems run in the dispatcher of the `FixedStepPhysicsSystem`, and `sync`
+/// runs as an unnamed thread local system after it. Use `with_system_at` to add systems
+/// alongside them instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhysicsSystemNames {
+    /// `PhysicsConfigReloadSystem`, only added with `from_config_path`
+    pub config_reload: String,
+    /// `TransformPoseSyncSystem`, run before the physics systems
+    pub transform_sync: String,
+    /// Current frame update, applying `NextFrame` values to the current frame
+    pub solver: String,
+    /// Next frame setup, integrating forces and velocities
+    pub next_frame_setup: String,
+    /// Spatial sorting, only added with `with_spatial`
+    pub spatial_sorting: String,
+    /// Collision detection
+    pub collision: String,
+    /// `ContactsSystem`, filling the `Contacts` components, only added with `with_contacts`
+    pub contacts: String,
+    /// `CollisionTrackingSystem`, only added with `with_collision_events`
+    pub collision_tracking: String,
+    /// `ImpactSystem`, sending `ImpactEvent`s, only added with `with_impacts`
+    pub impacts: String,
+    /// Contact resolution
+    pub contact_resolution: String,
+    /// `SensorEventSystem`, only added with `with_collision_events`
+    pub sensor_events: String,
+    /// `PoseTransformSyncSystem`, run after the physics systems
+    pub sync: String,
+}
+
+impl PhysicsSystemNames {
+    /// Names with the given prefix
+    pub fn with_prefix(prefix: &str) -> Self {
+        Self {
+            config_reload: format!("{}physics_config_reload", prefix),
+            transform_sync: format!("{}transform_sync_system", prefix),
+            solver: format!("{}physics_solver_system", prefix),
+            next_frame_setup: format!("{}next_frame_setup", prefix),
+            spatial_sorting: format!("{}spatial_sorting_system", prefix),
+            collision: format!("{}collision_system", prefix),
+            contacts: format!("{}contacts_system", prefix),
+            collision_tracking: format!("{}collision_tracking", prefix),
+            impacts: format!("{}impact_events", prefix),
+            contact_resolution: format!("{}contact_resolution", prefix),
+            sensor_events: format!("{}sensor_events", prefix),
+            sync: format!("{}sync_system", prefix),
+        }
+    }
+}
+
+impl Default for PhysicsSystemNames {
+    fn default() -> Self {
+        PhysicsSystemNames::with_prefix("")
+    }
+}
+
+type AddSystem = Box<dyn for<'x, 'y> FnOnce(&mut DispatcherBuilder<'x, 'y>, &[&str], bool)>;
+
+/// User systems waiting to be added at a `PhysicsStage`.
+pub(crate) struct StagedSystems {
+    systems: Vec<(PhysicsStage, String, AddSystem)>,
+}
+
+impl StagedSystems {
+    pub fn new() -> Self {
+        Self {
+            systems: Vec::new(),
+        }
+    }
+
+    pub fn push<S>(&mut self, stage: PhysicsStage, system: S, name: &str, dependencies: &[&str])
+    where
+        S: for<'c> System<'c> + Send + 'static,
+    {
+        let dependencies = dependencies
+            .iter()
+            .map(|dependency| dependency.to_string())
+            .collect::<Vec<_>>();
+        let system_name = name.to_string();
+        let add: AddSystem = Box::new(move |dispatcher, after, thread_local| {
+            if thread_local {
+                dispatcher.add_thread_local(system);
+            } else {
+                let mut dependencies = dependencies.iter().map(String::as_str).collect::<Vec<_>>();
+                dependencies.extend_from_slice(after);
+                dispatcher.add(system, &system_name, &dependencies);
+            }
+        });
+        self.systems.push((stage, name.to_string(), add));
+    }
+
+    /// Add the systems of a stage, running after the systems named in `after`.
+    ///
+    /// Returns the names the systems of the next stage should depend on.
+    pub fn add_stage(
+        &mut self,
+        stage: PhysicsStage,
+        dispatcher: &mut DispatcherBuilder<'_, '_>,
+        after: &[&str],
+    ) -> Vec<String> {
+        self.add_systems(stage, dispatcher, after, false)
+    }
+
+    /// Add the systems of a stage as thread local systems, running after the thread local
+    /// systems already added, in the order they were pushed.
+    pub fn add_stage_thread_local(
+        &mut self,
+        stage: PhysicsStage,
+        dispatcher: &mut DispatcherBuilder<'_, '_>,
+    ) {
+        self.add_systems(stage, dispatcher, &[], true);
+    }
+
+    fn add_systems(
+        &mut self,
+        stage: PhysicsStage,
+        dispatcher: &mut DispatcherBuilder<'_, '_>,
+        after: &[&str],
+        thread_local: bool,
+    ) -> Vec<String> {
+        let mut names = after.iter().map(|name| name.to_string()).collect::<Vec<_>>();
+        let (staged, rest) = self
+            .systems
+            .drain(..)
+            .partition::<Vec<_>, _>(|(system_stage, _, _)| *system_stage == stage);
+        self.systems = rest;
+        for (_, name, add) in staged {
+            add(dispatcher, after, thread_local);
+            names.push(name);
+        }
+        names
+    }
+}
+
+/// Borrow a list of system names, for passing as dependencies.
+pub(crate) fn as_strs(names: &[String]) -> Vec<&str> {
+    names.iter().map(String::as_str).collect()
+}
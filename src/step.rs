@@ -0,0 +1,341 @@
+use amethyst_core::ecs::{Dispatcher, DispatcherBuilder, RunNow, World, WorldExt};
+use amethyst_core::timing::Time;
+use amethyst_core::ArcThreadPool;
+use rhusics_ecs::DeltaTime;
+
+use crate::sync::InterpolationAlpha;
+
+/// Fixed time step state for the physics simulation.
+///
+/// Accumulates amethysts frame time, and hands it out in steps of equal length, so the
+/// simulation is independent of the frame rate. At most `max_substeps` steps are run in a single
+/// frame, any time beyond that is dropped, so a slow frame can't cause ever slower frames.
+#[derive(Debug, Clone)]
+pub struct FixedStepPhysics {
+    step: f32,
+    max_substeps: u32,
+    accumulator: f32,
+    substeps: u32,
+}
+
+impl FixedStepPhysics {
+    /// Create new fixed step state
+    ///
+    /// ### Parameters:
+    ///
+    /// - `step`: Length of a physics step in seconds
+    /// - `max_substeps`: Maximum number of physics steps to run in a single frame
+    ///
+    /// Panics if `step` isn't positive and finite, or if `max_substeps` is zero.
+    pub fn new(step: f32, max_substeps: u32) -> Self {
+        assert_valid_step(step);
+        assert_valid_max_substeps(max_substeps);
+        Self {
+            step,
+            max_substeps,
+            accumulator: 0.,
+            substeps: 0,
+        }
+    }
+
+    /// Length of a physics step in seconds
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Maximum number of physics steps to run in a single frame
+    pub fn max_substeps(&self) -> u32 {
+        self.max_substeps
+    }
+
+    /// Set length of a physics step in seconds
+    ///
+    /// Panics if `step` isn't positive and finite.
+    pub fn set_step(&mut self, step: f32) {
+        assert_valid_step(step);
+        self.step = step;
+    }
+
+    /// Set maximum number of physics steps to run in a single frame
+    ///
+    /// Panics if `max_substeps` is zero.
+    pub fn set_max_substeps(&mut self, max_substeps: u32) {
+        assert_valid_max_substeps(max_substeps);
+        self.max_substeps = max_substeps;
+    }
+
+    /// Number of physics steps run in the last frame
+    pub fn substeps(&self) -> u32 {
+        self.substeps
+    }
+
+    /// Fraction of a step that is left over in the accumulator
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.step
+    }
+
+    /// Keep the accumulator as is, and return the number of steps to run this frame, which is
+    /// one if `single_step` is set.
+    ///
+    /// Used instead of `advance` while the simulation is paused.
+    pub fn hold(&mut self, single_step: bool) -> u32 {
+        self.substeps = if single_step { 1 } else { 0 };
+        self.substeps
+    }
+
+    /// Add frame time to the accumulator, and return the number of steps to run this frame.
+    pub fn advance(&mut self, delta_seconds: f32) -> u32 {
+        self.accumulator += delta_seconds;
+        let substeps = (self.accumulator / self.step) as u32;
+        if substeps > self.max_substeps {
+            // Drop the time we can't catch up on, but keep the phase of the step
+            self.substeps = self.max_substeps;
+            self.accumulator %= self.step;
+        } else {
+            self.substeps = substeps;
+            self.accumulator -= substeps as f32 * self.step;
+        }
+        self.substeps
+    }
+}
+
+fn assert_valid_step(step: f32) {
+    assert!(
+        step.is_finite() && step > 0.,
+        "physics step must be positive and finite, got {}",
+        step
+    );
+}
+
+fn assert_valid_max_substeps(max_substeps: u32) {
+    assert!(
+        max_substeps >= 1,
+        "physics max substeps must be at least 1, got {}",
+        max_substeps
+    );
+}
+
+impl Default for FixedStepPhysics {
+    fn default() -> Self {
+        FixedStepPhysics::new(1. / 60., 8)
+    }
+}
+
+fn assert_valid_scale(scale: f32) {
+    assert!(
+        scale.is_finite() && scale >= 0.,
+        "physics time scale must be finite and not negative, got {}",
+        scale
+    );
+}
+
+/// Time scale for the physics simulation.
+///
+/// Used for slow motion, fast forward and pausing the physics simulation, while the rest of the
+/// game keeps running. Honoured by `time_sync` and `FixedStepPhysicsSystem`. While paused,
+/// `DeltaTime` is zero, so any forces added are kept until the simulation is resumed.
+#[derive(Debug, Clone)]
+pub struct PhysicsTimeScale {
+    scale: f32,
+    paused: bool,
+    pending_steps: u32,
+}
+
+impl PhysicsTimeScale {
+    /// Create new time scale
+    ///
+    /// Panics if `scale` is negative or not finite.
+    pub fn new(scale: f32) -> Self {
+        assert_valid_scale(scale);
+        Self {
+            scale,
+            paused: false,
+            pending_steps: 0,
+        }
+    }
+
+    /// Current scale factor, `1.` is real time
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Set scale factor
+    ///
+    /// Panics if `scale` is negative or not finite.
+    pub fn set_scale(&mut self, scale: f32) {
+        assert_valid_scale(scale);
+        self.scale = scale;
+    }
+
+    /// Is the simulation paused
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause the simulation
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resume the simulation, dropping any steps that haven't been run yet
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.pending_steps = 0;
+    }
+
+    /// Advance the simulation a single step while paused, on the next frame.
+    ///
+    /// Calling this multiple times will queue up one step per call, run one per frame.
+    pub fn step(&mut self) {
+        if self.paused {
+            self.pending_steps += 1;
+        }
+    }
+
+    /// Take a pending single step, if any
+    pub fn take_step(&mut self) -> bool {
+        if self.paused && self.pending_steps > 0 {
+            self.pending_steps -= 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Scale frame time to simulation time, taking any pending single step while paused.
+    pub fn scale_delta(&mut self, delta_seconds: f32) -> f32 {
+        if !self.paused || self.take_step() {
+            delta_seconds * self.scale
+        } else {
+            0.
+        }
+    }
+}
+
+impl Default for PhysicsTimeScale {
+    fn default() -> Self {
+        PhysicsTimeScale::new(1.)
+    }
+}
+
+/// Create a dispatcher builder for physics systems that run outside the main dispatcher,
+/// sharing amethysts thread pool when there is one.
+pub(crate) fn physics_dispatcher_builder(world: &World) -> DispatcherBuilder<'static, 'static> {
+    let builder = DispatcherBuilder::new();
+    match world.try_fetch::<ArcThreadPool>() {
+        Some(pool) => builder.with_pool((*pool).clone()),
+        None => builder,
+    }
+}
+
+/// Runs the physics systems in fixed time steps, using the `FixedStepPhysics` resource.
+///
+/// Sets `DeltaTime` to the step length, so `time_sync` should not be used together with this.
+/// Frame time is scaled by `PhysicsTimeScale`, and while paused only requested single steps are
+/// run. After stepping, `InterpolationAlpha` is updated for render interpolation.
+///
+/// This is a thread local system, so it runs after the parallel systems of the frame.
+pub struct FixedStepPhysicsSystem {
+    dispatcher: Dispatcher<'static, 'static>,
+}
+
+impl FixedStepPhysicsSystem {
+    /// Create new system, running the systems in the given dispatcher for each step
+    pub fn new(dispatcher: Dispatcher<'static, 'static>) -> Self {
+        Self { dispatcher }
+    }
+}
+
+impl<'a> RunNow<'a> for FixedStepPhysicsSystem {
+    fn run_now(&mut self, world: &'a World) {
+        let substeps = {
+            let delta_seconds = world.read_resource::<Time>().delta_seconds();
+            let mut fixed_step = world.write_resource::<FixedStepPhysics>();
+            let mut time_scale = world.write_resource::<PhysicsTimeScale>();
+            world.write_resource::<DeltaTime<f32>>().delta_seconds = fixed_step.step();
+            if time_scale.paused() {
+                let single_step = time_scale.take_step();
+                fixed_step.hold(single_step)
+            } else {
+                fixed_step.advance(delta_seconds * time_scale.scale())
+            }
+        };
+        for _ in 0..substeps {
+            self.dispatcher.dispatch(world);
+        }
+        world.write_resource::<InterpolationAlpha>().0 =
+            world.read_resource::<FixedStepPhysics>().alpha();
+    }
+
+    fn setup(&mut self, world: &mut World) {
+        world
+            .entry::<FixedStepPhysics>()
+            .or_insert_with(FixedStepPhysics::default);
+        world
+            .entry::<PhysicsTimeScale>()
+            .or_insert_with(PhysicsTimeScale::default);
+        world
+            .entry::<DeltaTime<f32>>()
+            .or_insert_with(DeltaTime::default);
+        world
+            .entry::<InterpolationAlpha>()
+            .or_insert_with(InterpolationAlpha::default);
+        self.dispatcher.setup(world);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_keeps_remainder() {
+        let mut fixed_step = FixedStepPhysics::new(0.1, 8);
+        assert_eq!(0, fixed_step.advance(0.05));
+        assert_eq!(1, fixed_step.advance(0.1));
+        assert!((fixed_step.alpha() - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_paused_scale_delta() {
+        let mut time_scale = PhysicsTimeScale::new(0.5);
+        assert_eq!(0.05, time_scale.scale_delta(0.1));
+        time_scale.pause();
+        assert_eq!(0., time_scale.scale_delta(0.1));
+        time_scale.step();
+        assert_eq!(0.05, time_scale.scale_delta(0.1));
+        assert_eq!(0., time_scale.scale_delta(0.1));
+    }
+
+    #[test]
+    fn test_advance_clamps_substeps() {
+        let mut fixed_step = FixedStepPhysics::new(0.1, 4);
+        assert_eq!(4, fixed_step.advance(1.05));
+        assert_eq!(4, fixed_step.substeps());
+        assert!(fixed_step.alpha() < 1.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_step() {
+        FixedStepPhysics::new(0., 8);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_zero_max_substeps() {
+        FixedStepPhysics::new(0.1, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_negative_scale() {
+        PhysicsTimeScale::new(1.).set_scale(-1.);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nan_scale() {
+        PhysicsTimeScale::new(std::f32::NAN);
+    }
+}
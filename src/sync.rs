@@ -1,17 +1,44 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
 use std::marker;
 
 use amethyst_core::math as na;
-use amethyst_core::ecs::{Join, ReadStorage, System, World, WorldExt, WriteStorage};
+use amethyst_core::ecs::prelude::{ParJoin, ParallelIterator};
+use amethyst_core::ecs::{
+    Component, DenseVecStorage, Entities, Entity, Join, Read, ReadStorage, System, World,
+    WorldExt, WriteStorage,
+};
 use amethyst_core::timing::Time;
-use amethyst_core::Transform;
-use cgmath::{Basis2, EuclideanSpace, Point2, Point3, Quaternion, Rotation};
-use rhusics_core::{BodyPose, Pose};
+use amethyst_core::{Parent, Transform};
+use cgmath::{
+    Basis2, EuclideanSpace, InnerSpace, Matrix2, Point2, Point3, Quaternion, Rad, Rotation,
+    Rotation2,
+};
+use rhusics_core::{BodyPose, NextFrame, Pose};
 use rhusics_ecs::DeltaTime;
 
+use crate::plane::Plane2Mapping;
+use crate::step::PhysicsTimeScale;
+use crate::units::PhysicsUnits;
+
 /// Utility trait for converting a transform type into an amethyst `Transform`.
 pub trait AsTransform {
     /// Convert to `Transform`
     fn as_transform(&self) -> Transform;
+
+    /// Convert to `Transform`, placing 2D quantities on the given plane
+    fn as_transform_on(&self, _plane: &Plane2Mapping) -> Transform {
+        self.as_transform()
+    }
+
+    /// Convert to `Transform`, with a visual offset composed onto it
+    fn as_transform_with_offset(&self, offset: &VisualOffset) -> Transform {
+        let mut t = self.as_transform();
+        let (translation, rotation) = offset.apply(t.translation(), t.rotation());
+        t.set_translation(translation);
+        t.set_rotation(rotation);
+        t
+    }
 }
 
 /// Utility trait for converting data between types.
@@ -23,6 +50,28 @@ pub trait Convert {
     type Output;
     /// Convert
     fn convert(&self, original: Self::Output) -> Self::Output;
+
+    /// Convert, placing 2D quantities on the given plane instead of the XY plane.
+    ///
+    /// 3D quantities ignore the plane.
+    fn convert_on(&self, original: Self::Output, _plane: &Plane2Mapping) -> Self::Output {
+        self.convert(original)
+    }
+}
+
+/// Utility trait for converting data back from amethysts internal data format.
+///
+/// This is the inverse of `Convert`, used when syncing a `Transform` back into a `BodyPose`.
+pub trait ConvertBack: Convert + Sized {
+    /// Convert back
+    fn convert_back(value: &Self::Output) -> Self;
+
+    /// Convert back, projecting onto the given plane instead of the XY plane.
+    ///
+    /// 3D quantities ignore the plane.
+    fn convert_back_on(value: &Self::Output, _plane: &Plane2Mapping) -> Self {
+        Self::convert_back(value)
+    }
 }
 
 impl Convert for Point2<f32> {
@@ -31,6 +80,20 @@ impl Convert for Point2<f32> {
     fn convert(&self, original: Self::Output) -> Self::Output {
         na::Vector3::new(self.x, self.y, original.z)
     }
+
+    fn convert_on(&self, original: Self::Output, plane: &Plane2Mapping) -> Self::Output {
+        plane.to_world(self, &original)
+    }
+}
+
+impl ConvertBack for Point2<f32> {
+    fn convert_back(value: &Self::Output) -> Self {
+        Point2::new(value.x, value.y)
+    }
+
+    fn convert_back_on(value: &Self::Output, plane: &Plane2Mapping) -> Self {
+        plane.to_plane(value)
+    }
 }
 
 impl Convert for Point3<f32> {
@@ -41,6 +104,12 @@ impl Convert for Point3<f32> {
     }
 }
 
+impl ConvertBack for Point3<f32> {
+    fn convert_back(value: &Self::Output) -> Self {
+        Point3::new(value.x, value.y, value.z)
+    }
+}
+
 impl Convert for Basis2<f32> {
     type Output = na::UnitQuaternion<f32>;
 
@@ -58,6 +127,23 @@ impl Convert for Basis2<f32> {
         );
         na::UnitQuaternion::from_rotation_matrix(&na::Rotation3::from_matrix_unchecked(matrix))
     }
+
+    fn convert_on(&self, _original: Self::Output, plane: &Plane2Mapping) -> Self::Output {
+        plane.rotation_to_world(self)
+    }
+}
+
+impl ConvertBack for Basis2<f32> {
+    fn convert_back(value: &Self::Output) -> Self {
+        // Mirror of `convert`, which places the basis columns in the rows of the rotation matrix.
+        let rotation = value.to_rotation_matrix();
+        let matrix = rotation.matrix();
+        Basis2::from_angle(Rad(matrix[(0, 1)].atan2(matrix[(0, 0)])))
+    }
+
+    fn convert_back_on(value: &Self::Output, plane: &Plane2Mapping) -> Self {
+        plane.rotation_to_plane(value)
+    }
 }
 
 impl Convert for Quaternion<f32> {
@@ -68,31 +154,357 @@ impl Convert for Quaternion<f32> {
     }
 }
 
+impl ConvertBack for Quaternion<f32> {
+    fn convert_back(value: &Self::Output) -> Self {
+        Quaternion::new(value.w, value.i, value.j, value.k)
+    }
+}
+
+/// Utility trait for blending between two poses, used for render interpolation.
+pub trait Interpolate {
+    /// Blend from `self` towards `other`, with `alpha` in the range `0..1`
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self;
+}
+
+impl Interpolate for Point2<f32> {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        *self + (*other - *self) * alpha
+    }
+}
+
+impl Interpolate for Point3<f32> {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        *self + (*other - *self) * alpha
+    }
+}
+
+impl Interpolate for Basis2<f32> {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        let from = basis_angle(self);
+        // Take the shortest way around
+        let delta = (basis_angle(other) - from + PI).rem_euclid(2. * PI) - PI;
+        Basis2::from_angle(Rad(from + delta * alpha))
+    }
+}
+
+impl Interpolate for Quaternion<f32> {
+    fn interpolate(&self, other: &Self, alpha: f32) -> Self {
+        // `q` and `-q` are the same rotation, take the one on the shortest way around
+        let other = if self.dot(*other) < 0. {
+            -*other
+        } else {
+            *other
+        };
+        self.slerp(other, alpha)
+    }
+}
+
+pub(crate) fn basis_angle(basis: &Basis2<f32>) -> f32 {
+    let matrix: &Matrix2<f32> = basis.as_ref();
+    matrix[0][1].atan2(matrix[0][0])
+}
+
 impl<P, R> AsTransform for BodyPose<P, R>
 where
     P: EuclideanSpace<Scalar = f32> + Convert<Output = na::Vector3<f32>>,
     R: Rotation<P> + Convert<Output = na::UnitQuaternion<f32>>,
 {
     fn as_transform(&self) -> Transform {
+        self.as_transform_on(&Plane2Mapping::default())
+    }
+
+    fn as_transform_on(&self, plane: &Plane2Mapping) -> Transform {
         let mut t = Transform::default();
         let original_translation = (*t.translation()).clone().into();
         let original_rotation = (*t.rotation()).clone();
-        t.set_translation(self.position().convert(original_translation));
-        t.set_rotation(self.rotation().convert(original_rotation));
+        t.set_translation(self.position().convert_on(original_translation, plane));
+        t.set_rotation(self.rotation().convert_on(original_rotation, plane));
         t
     }
 }
 
+/// Space of an entity with a `Parent`, used for converting between world space poses and
+/// local space `Transform`s.
+///
+/// Built from the global matrix of the parent, which is the one computed by amethysts
+/// transform system in the previous frame.
+struct ParentSpace {
+    matrix: na::Matrix4<f32>,
+    inverse: na::Matrix4<f32>,
+    rotation: na::UnitQuaternion<f32>,
+}
+
+impl ParentSpace {
+    fn new(matrix: &na::Matrix4<f32>) -> Option<Self> {
+        let inverse = matrix.try_inverse()?;
+        // Normalize the basis vectors to get rid of any scaling
+        let basis = matrix.fixed_slice::<na::U3, na::U3>(0, 0);
+        let basis = na::Matrix3::from_columns(&[
+            basis.column(0).normalize(),
+            basis.column(1).normalize(),
+            basis.column(2).normalize(),
+        ]);
+        Some(Self {
+            matrix: *matrix,
+            inverse,
+            rotation: na::UnitQuaternion::from_rotation_matrix(
+                &na::Rotation3::from_matrix_unchecked(basis),
+            ),
+        })
+    }
+
+    fn world_translation(&self, local: &na::Vector3<f32>) -> na::Vector3<f32> {
+        self.matrix.transform_point(&na::Point3::from(*local)).coords
+    }
+
+    fn local_translation(&self, world: &na::Vector3<f32>) -> na::Vector3<f32> {
+        self.inverse.transform_point(&na::Point3::from(*world)).coords
+    }
+
+    fn world_rotation(&self, local: &na::UnitQuaternion<f32>) -> na::UnitQuaternion<f32> {
+        self.rotation * local
+    }
+
+    fn local_rotation(&self, world: &na::UnitQuaternion<f32>) -> na::UnitQuaternion<f32> {
+        self.rotation.inverse() * world
+    }
+}
+
+fn parent_space(
+    entity: Entity,
+    parents: &ReadStorage<'_, Parent>,
+    transforms: &ReadStorage<'_, Transform>,
+) -> Option<ParentSpace> {
+    let parent = parents.get(entity)?;
+    ParentSpace::new(transforms.get(parent.entity)?.global_matrix())
+}
+
+/// Spaces of all parents of synced entities, by parent entity, so each parent matrix is only
+/// inverted once, and the transforms can be written to while reading them.
+fn parent_spaces<P, R>(
+    poses: &ReadStorage<'_, BodyPose<P, R>>,
+    parents: &ReadStorage<'_, Parent>,
+    transforms: &WriteStorage<'_, Transform>,
+) -> HashMap<Entity, ParentSpace>
+where
+    BodyPose<P, R>: Component,
+{
+    let mut spaces = HashMap::new();
+    for (_, parent) in (poses, parents).join() {
+        if spaces.contains_key(&parent.entity) {
+            continue;
+        }
+        let space = transforms
+            .get(parent.entity)
+            .and_then(|transform| ParentSpace::new(transform.global_matrix()));
+        if let Some(space) = space {
+            spaces.insert(parent.entity, space);
+        }
+    }
+    spaces
+}
+
+/// Tolerance when comparing synced values against a `Transform`, so the rounding of the round
+/// trip through the space of a parent isn't taken for a change.
+const SYNC_EPSILON: f32 = 1e-5;
+
+fn translation_changed(a: &na::Vector3<f32>, b: &na::Vector3<f32>) -> bool {
+    (a - b).norm() > SYNC_EPSILON * (1. + b.norm())
+}
+
+fn rotation_changed(a: &na::UnitQuaternion<f32>, b: &na::UnitQuaternion<f32>) -> bool {
+    a.angle_to(b) > SYNC_EPSILON
+}
+
 /// Utility function to sync time management from amethysts view of time, to rhusics view of time.
+///
+/// Frame time is scaled by the `PhysicsTimeScale` resource, if there is one.
+///
+/// This makes the simulation frame rate dependent. For a fixed time step, use
+/// `FixedStepPhysicsSystem` (`with_fixed_step` on the physics bundles) instead of this.
 pub fn time_sync(world: &World) {
     let mut delta = world.write_resource::<DeltaTime<f32>>();
     let time = world.read_resource::<Time>();
-    delta.delta_seconds = time.delta_seconds();
+    delta.delta_seconds = match world.try_fetch_mut::<PhysicsTimeScale>() {
+        Some(mut time_scale) => time_scale.scale_delta(time.delta_seconds()),
+        None => time.delta_seconds(),
+    };
+}
+
+/// How far, as a fraction of a physics step, rendering is between the current `BodyPose` and
+/// `NextFrame<BodyPose>`.
+///
+/// Should be set to the time left over after the last physics step, divided by the step length.
+/// Only used by `PoseTransformSyncSystem` when interpolation is enabled.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InterpolationAlpha(pub f32);
+
+/// Direction of transform syncing for an entity.
+///
+/// Entities without this component are synced from `BodyPose` to `Transform`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncDirection {
+    /// Physics drives rendering, handled by `PoseTransformSyncSystem`.
+    PoseToTransform,
+    /// `Transform` drives physics, handled by `TransformPoseSyncSystem`.
+    ///
+    /// Use for kinematic bodies moved by animation, scripts or an editor.
+    TransformToPose,
+}
+
+impl Default for SyncDirection {
+    fn default() -> Self {
+        SyncDirection::PoseToTransform
+    }
+}
+
+impl Component for SyncDirection {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Translation axis of a `Transform`, see `SyncMask::without_axis`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAxis {
+    /// The X axis
+    X = 0,
+    /// The Y axis
+    Y = 1,
+    /// The Z axis
+    Z = 2,
+}
+
+/// Per entity selection of what `PoseTransformSyncSystem` writes to the `Transform`.
+///
+/// Entities without this component use the flags of the system (`without_rotation` and
+/// `without_translation`). Translation axes are those of the `Transform`, and any axis that
+/// isn't synced keeps the value it already has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SyncMask {
+    /// Sync translation along the X, Y and Z axes
+    pub translation: [bool; 3],
+    /// Sync rotation
+    pub rotation: bool,
+}
+
+impl SyncMask {
+    /// Create new mask, syncing everything
+    pub fn new() -> Self {
+        Self {
+            translation: [true; 3],
+            rotation: true,
+        }
+    }
+
+    /// Disable rotation sync
+    pub fn without_rotation(mut self) -> Self {
+        self.rotation = false;
+        self
+    }
+
+    /// Disable translation sync
+    pub fn without_translation(mut self) -> Self {
+        self.translation = [false; 3];
+        self
+    }
+
+    /// Disable translation sync along a single axis
+    pub fn without_axis(mut self, axis: SyncAxis) -> Self {
+        self.translation[axis as usize] = false;
+        self
+    }
+
+    /// Does the mask sync translation along any axis
+    pub fn any_translation(&self) -> bool {
+        self.translation.iter().any(|axis| *axis)
+    }
+}
+
+impl Default for SyncMask {
+    fn default() -> Self {
+        SyncMask::new()
+    }
+}
+
+impl Component for SyncMask {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Offset of the rendered `Transform` from the physics pose of an entity, in render units.
+///
+/// Used when the pivot of a sprite or model isn't the center of its collision shape. The offset
+/// is in the space of the pose, so it rotates with the body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VisualOffset {
+    /// Translation from the pose to the `Transform`
+    pub translation: na::Vector3<f32>,
+    /// Rotation from the pose to the `Transform`
+    pub rotation: na::UnitQuaternion<f32>,
+}
+
+impl VisualOffset {
+    /// Create new offset
+    pub fn new(translation: na::Vector3<f32>, rotation: na::UnitQuaternion<f32>) -> Self {
+        Self {
+            translation,
+            rotation,
+        }
+    }
+
+    /// Create new offset, with translation only
+    pub fn from_translation(translation: na::Vector3<f32>) -> Self {
+        Self::new(translation, na::UnitQuaternion::identity())
+    }
+
+    /// Compose the offset onto a pose, giving the pose of the `Transform`
+    pub fn apply(
+        &self,
+        translation: &na::Vector3<f32>,
+        rotation: &na::UnitQuaternion<f32>,
+    ) -> (na::Vector3<f32>, na::UnitQuaternion<f32>) {
+        (
+            translation + rotation * self.translation,
+            rotation * self.rotation,
+        )
+    }
+
+    /// Remove the offset from the pose of a `Transform`, the inverse of `apply`
+    pub fn remove(
+        &self,
+        translation: &na::Vector3<f32>,
+        rotation: &na::UnitQuaternion<f32>,
+    ) -> (na::Vector3<f32>, na::UnitQuaternion<f32>) {
+        let rotation = rotation * self.rotation.inverse();
+        (translation - rotation * self.translation, rotation)
+    }
+}
+
+impl Default for VisualOffset {
+    fn default() -> Self {
+        VisualOffset::from_translation(na::Vector3::zeros())
+    }
+}
+
+impl Component for VisualOffset {
+    type Storage = DenseVecStorage<Self>;
 }
 
 /// System that copies transform information from `BodyPose` in rhusics into `Transform`
 /// in amethyst.
 ///
+/// Entities marked with `SyncDirection::TransformToPose` are skipped.
+///
+/// `BodyPose` is in world space, so for entities with a `Parent`, the pose is converted into the
+/// space of the parent, using the global matrix of the parent from the previous frame.
+///
+/// Positions are scaled from physics units to render units by the `PhysicsUnits` resource.
+/// What is synced can be selected per entity with the `SyncMask` component, and a
+/// `VisualOffset` is composed onto the pose before it is written.
+///
+/// A `Transform` is only written to when the synced values differ from what it already holds,
+/// so resting bodies don't flag their transforms as modified every frame. Static bodies, which
+/// have no `NextFrame<BodyPose>`, are only synced again when their `BodyPose` changes, changes
+/// to the parent, `SyncMask` or `VisualOffset` of a static body alone aren't picked up.
+///
 /// ### Type parameters:
 ///
 /// - `P`: Positional quantity (`Point2<f32>` or `Point3<f32>` in most scenarios).
@@ -101,6 +513,10 @@ pub struct PoseTransformSyncSystem<P, R> {
     m: marker::PhantomData<(P, R)>,
     translation: bool,
     rotation: bool,
+    interpolate: bool,
+    parallel: bool,
+    plane: Plane2Mapping,
+    static_poses: HashMap<Entity, (P, R)>,
 }
 
 impl<P, R> PoseTransformSyncSystem<P, R> {
@@ -110,9 +526,39 @@ impl<P, R> PoseTransformSyncSystem<P, R> {
             m: marker::PhantomData,
             translation: true,
             rotation: true,
+            interpolate: false,
+            parallel: false,
+            plane: Plane2Mapping::default(),
+            static_poses: HashMap::new(),
         }
     }
 
+    /// Enable render interpolation
+    ///
+    /// Blend between `BodyPose` and `NextFrame<BodyPose>` using the `InterpolationAlpha`
+    /// resource, instead of copying `BodyPose` as is. Entities without a `NextFrame<BodyPose>`
+    /// are copied as usual.
+    pub fn with_interpolation(mut self) -> Self {
+        self.interpolate = true;
+        self
+    }
+
+    /// Place 2D poses on the given plane, instead of the XY plane
+    ///
+    /// Has no effect on 3D poses.
+    pub fn with_plane(mut self, plane: Plane2Mapping) -> Self {
+        self.plane = plane;
+        self
+    }
+
+    /// Enable parallel sync
+    ///
+    /// Sync the bodies using `par_join`, which pays off with large numbers of moving bodies.
+    pub fn with_parallel(mut self) -> Self {
+        self.parallel = true;
+        self
+    }
+
     /// Disable rotation sync
     pub fn without_rotation(mut self) -> Self {
         self.rotation = false;
@@ -126,34 +572,483 @@ impl<P, R> PoseTransformSyncSystem<P, R> {
     }
 }
 
+/// Components of an entity that `PoseTransformSyncSystem` syncs from, besides the `Transform`.
+struct SyncSource<'e, P, R> {
+    parent: Option<&'e ParentSpace>,
+    pose: &'e BodyPose<P, R>,
+    next_pose: Option<&'e NextFrame<BodyPose<P, R>>>,
+    mask: Option<&'e SyncMask>,
+    offset: Option<&'e VisualOffset>,
+}
+
+impl<P, R> PoseTransformSyncSystem<P, R>
+where
+    P: EuclideanSpace<Scalar = f32> + Convert<Output = na::Vector3<f32>> + Interpolate,
+    R: Rotation<P> + Convert<Output = na::UnitQuaternion<f32>> + Interpolate,
+{
+    /// Has a static body been synced at its current pose
+    fn static_synced(&self, entity: Entity, pose: &BodyPose<P, R>) -> bool
+    where
+        P: PartialEq,
+        R: PartialEq,
+    {
+        self.static_poses.get(&entity) == Some(&(pose.position(), pose.rotation()))
+    }
+
+    /// Compute the synced translation and rotation of a `Transform`, or `None` if the
+    /// `Transform` is already up to date.
+    fn synced(
+        &self,
+        transform: &Transform,
+        source: SyncSource<'_, P, R>,
+        alpha: f32,
+        units: &PhysicsUnits,
+    ) -> Option<(na::Vector3<f32>, na::UnitQuaternion<f32>)> {
+        let mask = source.mask.cloned().unwrap_or(SyncMask {
+            translation: [self.translation; 3],
+            rotation: self.rotation,
+        });
+        if !mask.any_translation() && !mask.rotation {
+            return None;
+        }
+        let parent_space = source.parent;
+        let (position, rotation) = match source.next_pose.filter(|_| self.interpolate) {
+            Some(next_pose) => (
+                source
+                    .pose
+                    .position()
+                    .interpolate(&next_pose.value.position(), alpha),
+                source
+                    .pose
+                    .rotation()
+                    .interpolate(&next_pose.value.rotation(), alpha),
+            ),
+            None => (source.pose.position(), source.pose.rotation()),
+        };
+
+        // Current pose in world space, without the visual offset
+        let (mut translation, mut new_rotation) = (*transform.translation(), *transform.rotation());
+        if let Some(parent_space) = parent_space {
+            translation = parent_space.world_translation(&translation);
+            new_rotation = parent_space.world_rotation(&new_rotation);
+        }
+        if let Some(offset) = source.offset {
+            let (t, r) = offset.remove(&translation, &new_rotation);
+            translation = t;
+            new_rotation = r;
+        }
+
+        translation = units
+            .point_to_pixels(&position)
+            .convert_on(translation, &self.plane);
+        new_rotation = rotation.convert_on(new_rotation, &self.plane);
+
+        if let Some(offset) = source.offset {
+            let (t, r) = offset.apply(&translation, &new_rotation);
+            translation = t;
+            new_rotation = r;
+        }
+        if let Some(parent_space) = parent_space {
+            translation = parent_space.local_translation(&translation);
+            new_rotation = parent_space.local_rotation(&new_rotation);
+        }
+
+        for axis in 0..3 {
+            if !mask.translation[axis] {
+                translation[axis] = transform.translation()[axis];
+            }
+        }
+        if !mask.rotation {
+            new_rotation = *transform.rotation();
+        }
+        if translation_changed(&translation, transform.translation())
+            || rotation_changed(&new_rotation, transform.rotation())
+        {
+            Some((translation, new_rotation))
+        } else {
+            None
+        }
+    }
+}
+
 use std::fmt::Debug;
 
 impl<'a, P, R> System<'a> for PoseTransformSyncSystem<P, R>
 where
     P: Debug
+        + PartialEq
         + EuclideanSpace<Scalar = f32>
         + cgmath::EuclideanSpace<Scalar = f32>
         + Convert<Output = na::Vector3<f32>>
+        + Interpolate
+        + Send
+        + Sync
+        + 'static,
+    R: Debug
+        + PartialEq
+        + Rotation<P>
+        + Convert<Output = na::UnitQuaternion<f32>>
+        + Interpolate
         + Send
         + Sync
         + 'static,
-    R: Debug + Rotation<P> + Convert<Output = na::UnitQuaternion<f32>> + Send + Sync + 'static,
 {
-    type SystemData = (ReadStorage<'a, BodyPose<P, R>>, WriteStorage<'a, Transform>);
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, InterpolationAlpha>,
+        Read<'a, PhysicsUnits>,
+        ReadStorage<'a, SyncDirection>,
+        ReadStorage<'a, SyncMask>,
+        ReadStorage<'a, VisualOffset>,
+        ReadStorage<'a, Parent>,
+        ReadStorage<'a, BodyPose<P, R>>,
+        ReadStorage<'a, NextFrame<BodyPose<P, R>>>,
+        WriteStorage<'a, Transform>,
+    );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (poses, mut transforms) = data;
-        for (pose, transform) in (&poses, &mut transforms).join() {
-            if self.translation {
-                let original_translation = (*transform.translation()).into();
-                transform.set_translation(pose.position().convert(original_translation));
+        let (
+            entities,
+            alpha,
+            units,
+            directions,
+            masks,
+            offsets,
+            parents,
+            poses,
+            next_poses,
+            mut transforms,
+        ) = data;
+        let alpha = alpha.0;
+        let parent_spaces = parent_spaces(&poses, &parents, &transforms);
+        // Restricted storage, so reading a transform doesn't flag it as modified
+        if self.parallel {
+            (
+                &entities,
+                directions.maybe(),
+                &poses,
+                &mut transforms.par_restrict_mut(),
+            )
+                .par_join()
+                .for_each(|(entity, direction, pose, mut entry)| {
+                    let next_pose = next_poses.get(entity);
+                    if direction == Some(&SyncDirection::TransformToPose)
+                        || next_pose.is_none() && self.static_synced(entity, pose)
+                    {
+                        return;
+                    }
+                    let source = SyncSource {
+                        parent: parents
+                            .get(entity)
+                            .and_then(|parent| parent_spaces.get(&parent.entity)),
+                        pose,
+                        next_pose,
+                        mask: masks.get(entity),
+                        offset: offsets.get(entity),
+                    };
+                    let synced = self.synced(entry.get_unchecked(), source, alpha, &units);
+                    if let Some((translation, rotation)) = synced {
+                        let transform = entry.get_mut_unchecked();
+                        transform.set_translation(translation);
+                        transform.set_rotation(rotation);
+                    }
+                });
+        } else {
+            for (entity, direction, pose, mut entry) in (
+                &entities,
+                directions.maybe(),
+                &poses,
+                &mut transforms.restrict_mut(),
+            )
+                .join()
+            {
+                let next_pose = next_poses.get(entity);
+                if direction == Some(&SyncDirection::TransformToPose)
+                    || next_pose.is_none() && self.static_synced(entity, pose)
+                {
+                    continue;
+                }
+                let source = SyncSource {
+                    parent: parents
+                        .get(entity)
+                        .and_then(|parent| parent_spaces.get(&parent.entity)),
+                    pose,
+                    next_pose,
+                    mask: masks.get(entity),
+                    offset: offsets.get(entity),
+                };
+                let synced = self.synced(entry.get_unchecked(), source, alpha, &units);
+                if let Some((translation, rotation)) = synced {
+                    let transform = entry.get_mut_unchecked();
+                    transform.set_translation(translation);
+                    transform.set_rotation(rotation);
+                }
             }
-            if self.rotation {
-                let original_rotation = *transform.rotation();
-                transform.set_rotation(pose.rotation().convert(original_rotation));
+        }
+        // Rebuilt every frame, which also drops deleted entities
+        self.static_poses = (&entities, &poses, !&next_poses, &transforms)
+            .join()
+            .map(|(entity, pose, _, _)| (entity, (pose.position(), pose.rotation())))
+            .collect();
+    }
+}
+
+/// System that copies transform information from `Transform` in amethyst into `BodyPose` in
+/// rhusics, for entities marked with `SyncDirection::TransformToPose`.
+///
+/// Both the current `BodyPose` and `NextFrame<BodyPose>` are written, so the body stays where
+/// the `Transform` put it when the physics systems advance the frame.
+///
+/// For entities with a `Parent`, the `Transform` is converted into world space using the global
+/// matrix of the parent from the previous frame. Positions are scaled from render units to
+/// physics units by the `PhysicsUnits` resource, after removing any `VisualOffset`.
+///
+/// ### Type parameters:
+///
+/// - `P`: Positional quantity (`Point2<f32>` or `Point3<f32>` in most scenarios).
+/// - `R`: Rotational quantity (`Basis2<f32>` or `Quaternion<f32>` in most scenarios).
+pub struct TransformPoseSyncSystem<P, R> {
+    m: marker::PhantomData<(P, R)>,
+    translation: bool,
+    rotation: bool,
+    plane: Plane2Mapping,
+}
+
+impl<P, R> TransformPoseSyncSystem<P, R> {
+    /// Create new system
+    pub fn new() -> Self {
+        Self {
+            m: marker::PhantomData,
+            translation: true,
+            rotation: true,
+            plane: Plane2Mapping::default(),
+        }
+    }
+
+    /// Project `Transform`s onto the given plane for 2D poses, instead of the XY plane
+    ///
+    /// Has no effect on 3D poses.
+    pub fn with_plane(mut self, plane: Plane2Mapping) -> Self {
+        self.plane = plane;
+        self
+    }
+
+    /// Disable rotation sync
+    pub fn without_rotation(mut self) -> Self {
+        self.rotation = false;
+        self
+    }
+
+    /// Disable translation sync
+    pub fn without_translation(mut self) -> Self {
+        self.translation = false;
+        self
+    }
+
+    fn sync(
+        &self,
+        translation: &na::Vector3<f32>,
+        rotation: &na::UnitQuaternion<f32>,
+        units: &PhysicsUnits,
+        pose: &mut BodyPose<P, R>,
+    ) where
+        P: EuclideanSpace<Scalar = f32> + ConvertBack<Output = na::Vector3<f32>>,
+        R: Rotation<P> + ConvertBack<Output = na::UnitQuaternion<f32>>,
+    {
+        if self.translation {
+            let position = P::convert_back_on(translation, &self.plane);
+            pose.set_position(units.point_to_meters(&position));
+        }
+        if self.rotation {
+            pose.set_rotation(R::convert_back_on(rotation, &self.plane));
+        }
+    }
+}
+
+impl<P, R> Default for TransformPoseSyncSystem<P, R> {
+    fn default() -> Self {
+        TransformPoseSyncSystem::new()
+    }
+}
+
+impl<'a, P, R> System<'a> for TransformPoseSyncSystem<P, R>
+where
+    P: Debug
+        + EuclideanSpace<Scalar = f32>
+        + ConvertBack<Output = na::Vector3<f32>>
+        + Send
+        + Sync
+        + 'static,
+    R: Debug + Rotation<P> + ConvertBack<Output = na::UnitQuaternion<f32>> + Send + Sync + 'static,
+{
+    type SystemData = (
+        Entities<'a>,
+        Read<'a, PhysicsUnits>,
+        ReadStorage<'a, SyncDirection>,
+        ReadStorage<'a, VisualOffset>,
+        ReadStorage<'a, Parent>,
+        ReadStorage<'a, Transform>,
+        WriteStorage<'a, BodyPose<P, R>>,
+        WriteStorage<'a, NextFrame<BodyPose<P, R>>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            units,
+            directions,
+            offsets,
+            parents,
+            transforms,
+            mut poses,
+            mut next_poses,
+        ) = data;
+        for (entity, direction, transform, pose) in
+            (&entities, &directions, &transforms, &mut poses).join()
+        {
+            if *direction != SyncDirection::TransformToPose {
+                continue;
+            }
+            let (translation, rotation) = match parent_space(entity, &parents, &transforms) {
+                Some(parent_space) => (
+                    parent_space.world_translation(transform.translation()),
+                    parent_space.world_rotation(transform.rotation()),
+                ),
+                None => (*transform.translation(), *transform.rotation()),
+            };
+            let (translation, rotation) = match offsets.get(entity) {
+                Some(offset) => offset.remove(&translation, &rotation),
+                None => (translation, rotation),
+            };
+            self.sync(&translation, &rotation, &units, pose);
+            if let Some(next_pose) = next_poses.get_mut(entity) {
+                self.sync(&translation, &rotation, &units, &mut next_pose.value);
             }
-            //println!("T: {:?}", transform);
-            //println!("P: {:?}", pose);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use amethyst_core::ecs::{Builder, RunNow};
+    use cgmath::{Deg, Rotation3, Vector3};
+
+    use super::*;
+
+    #[test]
+    fn test_point_round_trip() {
+        let point = Point2::new(1., -2.);
+        let back = Point2::convert_back(&point.convert(na::Vector3::zeros()));
+        assert_eq!(point, back);
+        let point = Point3::new(1., -2., 3.);
+        let back = Point3::convert_back(&point.convert(na::Vector3::zeros()));
+        assert_eq!(point, back);
+    }
+
+    #[test]
+    fn test_rotation_round_trip() {
+        let basis = Basis2::from_angle(Rad(0.5));
+        let back = Basis2::convert_back(&basis.convert(na::UnitQuaternion::identity()));
+        assert!((basis_angle(&basis) - basis_angle(&back)).abs() < 1e-5);
+
+        let axis = Vector3::new(1., 2., 3.).normalize();
+        let quaternion = Quaternion::from_axis_angle(axis, Deg(40.));
+        let back = Quaternion::convert_back(&quaternion.convert(na::UnitQuaternion::identity()));
+        assert!((quaternion - back).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn test_quaternion_shortest_path() {
+        let from = Quaternion::from_angle_z(Deg(10.));
+        // Same rotation as 30 degrees, on the other side of the hypersphere
+        let to = -Quaternion::from_angle_z(Deg(30.));
+        let halfway = from.interpolate(&to, 0.5);
+        let expected = Quaternion::from_angle_z(Deg(20.));
+        assert!((halfway - expected).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn test_parent_space_round_trip() {
+        let rotation = na::UnitQuaternion::from_axis_angle(&na::Vector3::z_axis(), 0.7);
+        let matrix = na::Matrix4::new_translation(&na::Vector3::new(1., 2., 3.))
+            * rotation.to_homogeneous()
+            * na::Matrix4::new_nonuniform_scaling(&na::Vector3::new(2., 3., 4.));
+        let space = ParentSpace::new(&matrix).unwrap();
+        assert!(space.rotation.angle_to(&rotation) < 1e-5);
+
+        let translation = na::Vector3::new(-4., 5., 6.);
+        let back = space.local_translation(&space.world_translation(&translation));
+        assert!(!translation_changed(&back, &translation));
+        let local = na::UnitQuaternion::from_axis_angle(&na::Vector3::x_axis(), 0.3);
+        let back = space.local_rotation(&space.world_rotation(&local));
+        assert!(!rotation_changed(&back, &local));
+    }
+
+    #[test]
+    fn test_visual_offset_round_trip() {
+        let offset = VisualOffset::new(
+            na::Vector3::new(1., -2., 0.5),
+            na::UnitQuaternion::from_axis_angle(&na::Vector3::z_axis(), 0.4),
+        );
+        let translation = na::Vector3::new(3., 4., 5.);
+        let rotation = na::UnitQuaternion::from_axis_angle(&na::Vector3::y_axis(), 1.2);
+        let (t, r) = offset.apply(&translation, &rotation);
+        let (t, r) = offset.remove(&t, &r);
+        assert!(!translation_changed(&t, &translation));
+        assert!(!rotation_changed(&r, &rotation));
+    }
+
+    #[test]
+    fn test_sync_mask() {
+        let mut world = World::new();
+        let mut system =
+            PoseTransformSyncSystem::<Point2<f32>, Basis2<f32>>::new().without_rotation();
+        System::setup(&mut system, &mut world);
+        let pose = BodyPose::new(Point2::new(3., 4.), Basis2::from_angle(Rad(0.5)));
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(0., 7., 0.);
+        let masked = world
+            .create_entity()
+            .with(transform.clone())
+            .with(pose.clone())
+            .with(SyncMask::new().without_axis(SyncAxis::Y))
+            .build();
+        let unmasked = world.create_entity().with(transform).with(pose).build();
+        system.run_now(&world);
+
+        let transforms = world.read_storage::<Transform>();
+        let masked = transforms.get(masked).unwrap();
+        assert_eq!(&na::Vector3::new(3., 7., 0.), masked.translation());
+        assert!((masked.rotation().angle() - 0.5).abs() < 1e-5);
+        // Falls back to the flags of the system
+        let unmasked = transforms.get(unmasked).unwrap();
+        assert_eq!(&na::Vector3::new(3., 4., 0.), unmasked.translation());
+        assert_eq!(&na::UnitQuaternion::identity(), unmasked.rotation());
+    }
+
+    #[test]
+    fn test_transform_to_pose() {
+        let mut world = World::new();
+        let mut system = TransformPoseSyncSystem::<Point2<f32>, Basis2<f32>>::default();
+        System::setup(&mut system, &mut world);
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(3., 4., 0.);
+        let pose = BodyPose::new(Point2::origin(), Basis2::from_angle(Rad(0.)));
+        let entity = world
+            .create_entity()
+            .with(transform)
+            .with(SyncDirection::TransformToPose)
+            .with(pose.clone())
+            .with(NextFrame { value: pose })
+            .build();
+        system.run_now(&world);
+
+        let poses = world.read_storage::<BodyPose<Point2<f32>, Basis2<f32>>>();
+        let next_poses = world.read_storage::<NextFrame<BodyPose<Point2<f32>, Basis2<f32>>>>();
+        assert_eq!(Point2::new(3., 4.), poses.get(entity).unwrap().position());
+        assert_eq!(
+            Point2::new(3., 4.),
+            next_poses.get(entity).unwrap().value.position()
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use cgmath::EuclideanSpace;
+
+/// Scale between physics units and render units.
+///
+/// Lets the physics run in SI units (meters), while rendering happens in pixels, which keeps
+/// gravity meaningful and the solver working with sensible magnitudes. Applied by the sync
+/// systems and the arena setup functions. Defaults to one pixel per meter, which is the same as
+/// not scaling at all.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PhysicsUnits {
+    pixels_per_meter: f32,
+}
+
+impl PhysicsUnits {
+    /// Create new physics units
+    ///
+    /// ### Parameters:
+    ///
+    /// - `pixels_per_meter`: Number of render units per physics unit
+    ///
+    /// Panics if `pixels_per_meter` isn't positive and finite.
+    pub fn new(pixels_per_meter: f32) -> Self {
+        assert!(
+            pixels_per_meter.is_finite() && pixels_per_meter > 0.,
+            "pixels per meter must be positive and finite, got {}",
+            pixels_per_meter
+        );
+        Self { pixels_per_meter }
+    }
+
+    /// Number of render units per physics unit
+    pub fn pixels_per_meter(&self) -> f32 {
+        self.pixels_per_meter
+    }
+
+    /// Convert a length in physics units to render units
+    pub fn to_pixels(&self, meters: f32) -> f32 {
+        meters * self.pixels_per_meter
+    }
+
+    /// Convert a length in render units to physics units
+    pub fn to_meters(&self, pixels: f32) -> f32 {
+        pixels / self.pixels_per_meter
+    }
+
+    /// Convert a position in physics units to render units
+    pub fn point_to_pixels<P>(&self, point: &P) -> P
+    where
+        P: EuclideanSpace<Scalar = f32>,
+    {
+        P::from_vec(point.to_vec() * self.pixels_per_meter)
+    }
+
+    /// Convert a position in render units to physics units
+    pub fn point_to_meters<P>(&self, point: &P) -> P
+    where
+        P: EuclideanSpace<Scalar = f32>,
+    {
+        P::from_vec(point.to_vec() / self.pixels_per_meter)
+    }
+}
+
+impl Default for PhysicsUnits {
+    fn default() -> Self {
+        PhysicsUnits::new(1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic]
+    fn test_zero_pixels_per_meter() {
+        PhysicsUnits::new(0.);
+    }
+}